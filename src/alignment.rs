@@ -1,10 +1,25 @@
+pub mod msa;
 pub mod pairwise;
+pub mod profile_hmm;
+pub mod pssm;
 
+/// A pairwise alignment of `x` and `y`. With the `serde` feature, it can be serialized
+/// with the sequences written as strings; see
+/// [`OwnedAlignmentResult`](struct.OwnedAlignmentResult.html) to deserialize it.
 #[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct AlignmentResult<'a> {
     pub alignment: Vec<AlignmentOperation>,
     pub score: i32,
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::utils::serde_text::serialize")
+    )]
     pub x: &'a [u8],
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::utils::serde_text::serialize")
+    )]
     pub y: &'a [u8],
     pub xstart: usize,
     pub ystart: usize,
@@ -13,6 +28,20 @@ pub struct AlignmentResult<'a> {
 }
 
 impl<'a> AlignmentResult<'a> {
+    /// Copy the sequences, so that the alignment no longer borrows them
+    pub fn into_owned(self) -> OwnedAlignmentResult {
+        OwnedAlignmentResult {
+            alignment: self.alignment,
+            score: self.score,
+            x: self.x.to_vec(),
+            y: self.y.to_vec(),
+            xstart: self.xstart,
+            ystart: self.ystart,
+            xend: self.xend,
+            yend: self.yend,
+        }
+    }
+
     pub fn as_strings(&self, gap_char: char) -> (String, String) {
         let gap_char = gap_char as u8;
         let mut x: Vec<u8> = Vec::with_capacity(self.alignment.len());
@@ -48,6 +77,40 @@ impl<'a> AlignmentResult<'a> {
     }
 }
 
+/// An [`AlignmentResult`](struct.AlignmentResult.html) owning its sequences, which can
+/// be stored, or deserialized with the `serde` feature
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedAlignmentResult {
+    pub alignment: Vec<AlignmentOperation>,
+    pub score: i32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_text"))]
+    pub x: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_text"))]
+    pub y: Vec<u8>,
+    pub xstart: usize,
+    pub ystart: usize,
+    pub xend: usize,
+    pub yend: usize,
+}
+
+impl OwnedAlignmentResult {
+    /// Borrow the alignment, e.g. to format it with
+    /// [`as_strings`](struct.AlignmentResult.html#method.as_strings)
+    pub fn as_result(&self) -> AlignmentResult<'_> {
+        AlignmentResult {
+            alignment: self.alignment.clone(),
+            score: self.score,
+            x: &self.x,
+            y: &self.y,
+            xstart: self.xstart,
+            ystart: self.ystart,
+            xend: self.xend,
+            yend: self.yend,
+        }
+    }
+}
+
 /// Trait required to instantiate a Scoring instance
 pub trait MatchFunc {
     fn score(&self, a: u8, b: u8) -> Score;
@@ -210,6 +273,7 @@ impl<F: MatchFunc> Scoring<F> {
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AlignmentOperation {
     Del,   // up
     Ins,   // left
@@ -243,3 +307,30 @@ pub fn max_score_and_operation_precomputed(
     }
     (max, operation)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alignment::pairwise::nw_se::NwSpaceEfficientAligner;
+
+    #[test]
+    fn test_owned() {
+        let scoring = Scoring::from_scores(0, -1, 1, -1);
+        let owned = NwSpaceEfficientAligner::new(scoring)
+            .global(b"ACGTTA", b"ACTTA")
+            .into_owned();
+        assert_eq!(
+            owned.as_result().as_strings('-'),
+            ("ACGTTA".into(), "AC-TTA".into())
+        );
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&owned).unwrap();
+            assert!(json.contains(r#""x":"ACGTTA","y":"ACTTA""#));
+            assert_eq!(
+                serde_json::from_str::<OwnedAlignmentResult>(&json).unwrap(),
+                owned
+            );
+        }
+    }
+}
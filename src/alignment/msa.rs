@@ -0,0 +1,131 @@
+//! Multiple sequence alignments (MSA).
+//!
+//! An [Msa](struct.Msa.html) holds a set of named, equal-length rows of aligned
+//! residues. Gaps are represented by `-` (or `.`, as used by Stockholm for
+//! insert columns). Readers and writers for common exchange formats live in
+//! [io](io/index.html).
+//!
+//! # Example
+//!
+//! ```
+//! use bioinformatics_algorithms::alignment::msa::Msa;
+//! let mut msa = Msa::new();
+//! msa.push("seq1", b"ATG-CA".to_vec());
+//! msa.push("seq2", b"ATGGCA".to_vec());
+//! assert_eq!(msa.ncols(), 6);
+//! assert_eq!(msa.column(3), vec![b'-', b'G']);
+//! ```
+
+pub mod io;
+pub mod stats;
+
+use crate::utils::TextSlice;
+use std::collections::BTreeMap;
+
+/// The canonical gap character
+pub const GAP: u8 = b'-';
+
+/// Whether a byte denotes a gap (`-` or `.`)
+#[inline]
+pub fn is_gap(c: u8) -> bool {
+    c == b'-' || c == b'.'
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Msa {
+    pub names: Vec<String>,
+    pub seqs: Vec<Vec<u8>>,
+    /// Per-column annotations (e.g. Stockholm `#=GC SS_cons`), each as long as the alignment
+    pub column_annotations: BTreeMap<String, Vec<u8>>,
+}
+
+impl Msa {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a row to the alignment.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `seq` does not have the same length as the existing rows.
+    pub fn push<S: Into<String>>(&mut self, name: S, seq: Vec<u8>) {
+        if let Some(first) = self.seqs.first() {
+            assert_eq!(
+                first.len(),
+                seq.len(),
+                "all rows of an alignment must have the same length"
+            );
+        }
+        self.names.push(name.into());
+        self.seqs.push(seq);
+    }
+
+    /// Number of sequences (rows)
+    pub fn len(&self) -> usize {
+        self.seqs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seqs.is_empty()
+    }
+
+    /// Number of alignment columns
+    pub fn ncols(&self) -> usize {
+        self.seqs.first().map_or(0, |s| s.len())
+    }
+
+    pub fn seq(&self, i: usize) -> TextSlice<'_> {
+        &self.seqs[i]
+    }
+
+    /// Residues of column `j`, one per row
+    pub fn column(&self, j: usize) -> Vec<u8> {
+        self.seqs.iter().map(|s| s[j]).collect()
+    }
+
+    /// Iterate over `(name, aligned sequence)` pairs
+    pub fn iter(&self) -> impl Iterator<Item = (&str, TextSlice<'_>)> {
+        self.names
+            .iter()
+            .zip(self.seqs.iter())
+            .map(|(n, s)| (n.as_str(), s.as_slice()))
+    }
+
+    /// Row `i` with all gaps removed
+    pub fn ungapped(&self, i: usize) -> Vec<u8> {
+        self.seqs[i]
+            .iter()
+            .copied()
+            .filter(|&c| !is_gap(c))
+            .collect()
+    }
+
+    /// Check that all rows and column annotations have the same length.
+    pub fn is_valid(&self) -> bool {
+        let n = self.ncols();
+        self.seqs.iter().all(|s| s.len() == n)
+            && self.column_annotations.values().all(|a| a.len() == n)
+    }
+
+    /// Keep only the columns `j` for which `keep[j]` is `true`, including in the
+    /// column annotations.
+    pub fn select_columns(&self, keep: &[bool]) -> Self {
+        let filter = |s: &Vec<u8>| -> Vec<u8> {
+            s.iter()
+                .zip(keep.iter())
+                .filter(|(_, &k)| k)
+                .map(|(&c, _)| c)
+                .collect()
+        };
+        Msa {
+            names: self.names.clone(),
+            seqs: self.seqs.iter().map(filter).collect(),
+            column_annotations: self
+                .column_annotations
+                .iter()
+                .map(|(k, v)| (k.clone(), filter(v)))
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! Readers and writers for multiple alignment file formats.
+//!
+//! Each format lives in its own module and exposes a `read` function taking any
+//! `BufRead` and a `write` function taking any `Write`:
+//!
+//! - [clustal](clustal/index.html): Clustal `.aln`
+//! - [fasta](fasta/index.html): aligned FASTA
+//! - [stockholm](stockholm/index.html): Stockholm 1.0, including `#=GC` annotations
+//! - [phylip](phylip/index.html): PHYLIP, sequential and interleaved
+//!
+//! # Example
+//!
+//! ```
+//! use bioinformatics_algorithms::alignment::msa::io::{clustal, fasta};
+//! let aln = b">a\nAC-GT\n>b\nACGGT\n";
+//! let msa = fasta::read(&aln[..]).unwrap();
+//! let mut out = Vec::new();
+//! clustal::write(&msa, &mut out).unwrap();
+//! assert_eq!(clustal::read(&out[..]).unwrap(), msa);
+//! ```
+
+pub mod clustal;
+pub mod fasta;
+pub mod phylip;
+pub mod stockholm;
+
+use crate::alignment::msa::Msa;
+use std::io;
+
+pub(crate) fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(msg: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Build an `Msa` from rows collected in order of first appearance, checking that
+/// all rows have the same length.
+fn msa_from_rows(rows: Vec<(String, Vec<u8>)>) -> io::Result<Msa> {
+    let mut msa = Msa::new();
+    for (name, seq) in rows {
+        if !msa.is_empty() && seq.len() != msa.ncols() {
+            return Err(invalid_data(format!(
+                "sequence '{}' has length {}, expected {}",
+                name,
+                seq.len(),
+                msa.ncols()
+            )));
+        }
+        msa.push(name, seq);
+    }
+    Ok(msa)
+}
+
+/// Width of the name column needed to fit all names plus some padding
+fn name_width(msa: &Msa, padding: usize) -> usize {
+    msa.names.iter().map(|n| n.len()).max().unwrap_or(0) + padding
+}
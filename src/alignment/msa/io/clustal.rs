@@ -0,0 +1,163 @@
+//! Clustal (`.aln`) format.
+//!
+//! The file starts with a header line beginning with `CLUSTAL` (files produced by
+//! MUSCLE or T-Coffee start with their own name and are accepted too), followed by
+//! blocks of `name residues [cumulative count]` lines. Conservation lines (which
+//! start with whitespace) and blank lines are ignored when reading. When writing,
+//! the conservation line is computed with Clustal's symbols:
+//!
+//! - `*`: fully conserved column
+//! - `:`: all residues belong to one of the "strong" groups
+//! - `.`: all residues belong to one of the "weak" groups
+
+use super::{invalid_data, msa_from_rows, name_width};
+use crate::alignment::msa::{is_gap, Msa};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+/// Number of residues per line when writing
+pub const LINE_WIDTH: usize = 60;
+
+const STRONG_GROUPS: [&[u8]; 9] = [
+    b"STA", b"NEQK", b"NHQK", b"NDEQ", b"QHRK", b"MILV", b"MILF", b"HY", b"FYW",
+];
+const WEAK_GROUPS: [&[u8]; 11] = [
+    b"CSA", b"ATV", b"SAG", b"STNK", b"STPA", b"SGND", b"SNDEQK", b"NDEQHK", b"NEQHRK", b"FVLIM",
+    b"HFY",
+];
+
+pub fn read<R: BufRead>(reader: R) -> io::Result<Msa> {
+    let mut lines = reader.lines();
+    match lines.next() {
+        Some(line) => {
+            let line = line?;
+            let header = line.trim_start();
+            if !(header.starts_with("CLUSTAL")
+                || header.starts_with("MUSCLE")
+                || header.starts_with("T-COFFEE"))
+            {
+                return Err(invalid_data("missing CLUSTAL header"));
+            }
+        }
+        None => return Err(invalid_data("empty input")),
+    }
+    let mut rows: Vec<(String, Vec<u8>)> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for line in lines {
+        let line = line?;
+        if line.trim().is_empty() || line.starts_with(char::is_whitespace) {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let name = fields.next().unwrap();
+        let residues = fields
+            .next()
+            .ok_or_else(|| invalid_data(format!("no residues for sequence '{}'", name)))?;
+        let i = *index.entry(name.to_owned()).or_insert_with(|| {
+            rows.push((name.to_owned(), Vec::new()));
+            rows.len() - 1
+        });
+        rows[i].1.extend_from_slice(residues.as_bytes());
+    }
+    msa_from_rows(rows)
+}
+
+pub fn write<W: Write>(msa: &Msa, mut writer: W) -> io::Result<()> {
+    writeln!(writer, "CLUSTAL W multiple sequence alignment")?;
+    writeln!(writer)?;
+    let width = name_width(msa, 6);
+    let conservation = conservation_line(msa);
+    let ncols = msa.ncols();
+    let mut start = 0;
+    while start < ncols {
+        writeln!(writer)?;
+        let end = (start + LINE_WIDTH).min(ncols);
+        for (name, seq) in msa.iter() {
+            writeln!(
+                writer,
+                "{:width$}{}",
+                name,
+                String::from_utf8_lossy(&seq[start..end]),
+                width = width
+            )?;
+        }
+        writeln!(
+            writer,
+            "{:width$}{}",
+            "",
+            String::from_utf8_lossy(&conservation[start..end]),
+            width = width
+        )?;
+        start = end;
+    }
+    Ok(())
+}
+
+/// Compute the Clustal conservation symbol (`*`, `:`, `.` or space) of each column.
+pub fn conservation_line(msa: &Msa) -> Vec<u8> {
+    (0..msa.ncols())
+        .map(|j| {
+            let col: Vec<u8> = msa
+                .column(j)
+                .into_iter()
+                .map(|c| c.to_ascii_uppercase())
+                .collect();
+            if col.is_empty() || col.iter().any(|&c| is_gap(c)) {
+                b' '
+            } else if col.iter().all(|&c| c == col[0]) {
+                b'*'
+            } else if in_one_group(&col, &STRONG_GROUPS) {
+                b':'
+            } else if in_one_group(&col, &WEAK_GROUPS) {
+                b'.'
+            } else {
+                b' '
+            }
+        })
+        .collect()
+}
+
+fn in_one_group(col: &[u8], groups: &[&[u8]]) -> bool {
+    groups
+        .iter()
+        .any(|group| col.iter().all(|c| group.contains(c)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static ALN: &[u8] = b"CLUSTAL W (1.83) multiple sequence alignment
+
+
+seq1      MKV-LAAG 7
+seq2      MRVELSAG 8
+          *:* *:**
+
+seq1      WT 9
+seq2      WV 10
+          *.
+";
+
+    #[test]
+    fn test_read() {
+        let msa = read(ALN).unwrap();
+        assert_eq!(msa.names, vec!["seq1", "seq2"]);
+        assert_eq!(msa.seqs[0], b"MKV-LAAGWT".to_vec());
+        assert_eq!(msa.seqs[1], b"MRVELSAGWV".to_vec());
+    }
+
+    #[test]
+    fn test_conservation_line() {
+        let msa = read(ALN).unwrap();
+        assert_eq!(conservation_line(&msa), b"*:* *:***.".to_vec());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let msa = read(ALN).unwrap();
+        let mut out = Vec::new();
+        write(&msa, &mut out).unwrap();
+        assert_eq!(read(&out[..]).unwrap(), msa);
+    }
+}
@@ -0,0 +1,26 @@
+//! Aligned FASTA format, i.e. ordinary FASTA in which all records have the same
+//! length and gaps are written as `-`.
+//!
+//! Only the record identifier is kept as the row name; descriptions are dropped.
+
+use super::msa_from_rows;
+use crate::alignment::msa::Msa;
+use bio::io::fasta;
+use std::io::{self, BufRead, Write};
+
+pub fn read<R: BufRead>(reader: R) -> io::Result<Msa> {
+    let mut rows = Vec::new();
+    for record in fasta::Reader::new(reader).records() {
+        let record = record?;
+        rows.push((record.id().to_owned(), record.seq().to_vec()));
+    }
+    msa_from_rows(rows)
+}
+
+pub fn write<W: Write>(msa: &Msa, writer: W) -> io::Result<()> {
+    let mut writer = fasta::Writer::new(writer);
+    for (name, seq) in msa.iter() {
+        writer.write(name, None, seq)?;
+    }
+    writer.flush()
+}
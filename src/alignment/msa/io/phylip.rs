@@ -0,0 +1,179 @@
+//! PHYLIP format, in both sequential and interleaved layouts.
+//!
+//! The first line gives the number of sequences and the number of columns. Names
+//! follow the strict PHYLIP convention and occupy the first 10 characters of a
+//! sequence's first line; longer names are truncated when writing. Whitespace
+//! within residues is ignored when reading.
+//!
+//! # References
+//!
+//! - [PHYLIP documentation: sequence input format](https://evolution.genetics.washington.edu/phylip/doc/sequence.html)
+
+use super::{invalid_data, msa_from_rows};
+use crate::alignment::msa::Msa;
+use std::io::{self, BufRead, Write};
+
+/// Width of the name field
+pub const NAME_WIDTH: usize = 10;
+/// Number of residues per line when writing interleaved files
+pub const LINE_WIDTH: usize = 60;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Layout {
+    /// Each sequence is given in full (possibly over several lines) before the next
+    Sequential,
+    /// Sequences are given in blocks; only the first block carries names
+    Interleaved,
+}
+
+pub fn read<R: BufRead>(reader: R, layout: Layout) -> io::Result<Msa> {
+    let mut lines = reader.lines();
+    let header = loop {
+        match lines.next() {
+            Some(line) => {
+                let line = line?;
+                if !line.trim().is_empty() {
+                    break line;
+                }
+            }
+            None => return Err(invalid_data("empty input")),
+        }
+    };
+    let mut fields = header.split_whitespace();
+    let mut parse_field = || -> io::Result<usize> {
+        fields
+            .next()
+            .and_then(|f| f.parse().ok())
+            .ok_or_else(|| invalid_data("the header must give the number of sequences and columns"))
+    };
+    let ntax = parse_field()?;
+    let nchar = parse_field()?;
+
+    let mut rows: Vec<(String, Vec<u8>)> = Vec::with_capacity(ntax);
+    let mut current = 0; // index of the sequence the next line belongs to
+    for line in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if rows.len() < ntax && (layout == Layout::Interleaved || current == rows.len()) {
+            let split = line
+                .char_indices()
+                .nth(NAME_WIDTH)
+                .map_or(line.len(), |(i, _)| i);
+            let (name, residues) = line.split_at(split);
+            rows.push((name.trim().to_owned(), Vec::with_capacity(nchar)));
+            extend_residues(&mut rows.last_mut().unwrap().1, residues);
+        } else if current < rows.len() {
+            extend_residues(&mut rows[current].1, &line);
+        } else {
+            return Err(invalid_data("more sequences than declared in the header"));
+        }
+        current = match layout {
+            Layout::Sequential if rows[current].1.len() >= nchar => current + 1,
+            Layout::Sequential => current,
+            Layout::Interleaved => (current + 1) % ntax,
+        };
+    }
+    if rows.len() != ntax {
+        return Err(invalid_data(format!(
+            "expected {} sequences, found {}",
+            ntax,
+            rows.len()
+        )));
+    }
+    if let Some((name, seq)) = rows.iter().find(|(_, seq)| seq.len() != nchar) {
+        return Err(invalid_data(format!(
+            "sequence '{}' has length {}, expected {}",
+            name,
+            seq.len(),
+            nchar
+        )));
+    }
+    msa_from_rows(rows)
+}
+
+fn extend_residues(seq: &mut Vec<u8>, residues: &str) {
+    seq.extend(residues.bytes().filter(|c| !c.is_ascii_whitespace()));
+}
+
+pub fn write<W: Write>(msa: &Msa, mut writer: W, layout: Layout) -> io::Result<()> {
+    writeln!(writer, " {} {}", msa.len(), msa.ncols())?;
+    let ncols = msa.ncols();
+    let line_width = match layout {
+        Layout::Sequential => ncols.max(1),
+        Layout::Interleaved => LINE_WIDTH,
+    };
+    for (name, seq) in msa.iter() {
+        let name: String = name.chars().take(NAME_WIDTH).collect();
+        let end = line_width.min(ncols);
+        writeln!(
+            writer,
+            "{:width$}{}",
+            name,
+            String::from_utf8_lossy(&seq[..end]),
+            width = NAME_WIDTH
+        )?;
+    }
+    let mut start = line_width;
+    while start < ncols {
+        writeln!(writer)?;
+        let end = (start + line_width).min(ncols);
+        for seq in msa.seqs.iter() {
+            writeln!(
+                writer,
+                "{:width$}{}",
+                "",
+                String::from_utf8_lossy(&seq[start..end]),
+                width = NAME_WIDTH
+            )?;
+        }
+        start = end;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_sequential() {
+        let phy = b" 2 12
+Turkey    AAGCTNGGGC
+          AT
+Salmo gairAAGCCTTGGC
+AG
+";
+        let msa = read(&phy[..], Layout::Sequential).unwrap();
+        assert_eq!(msa.names, vec!["Turkey", "Salmo gair"]);
+        assert_eq!(msa.seqs[0], b"AAGCTNGGGCAT".to_vec());
+        assert_eq!(msa.seqs[1], b"AAGCCTTGGCAG".to_vec());
+    }
+
+    #[test]
+    fn test_read_interleaved() {
+        let phy = b" 2 12
+Turkey    AAGCT NGGGC
+Salmo gairAAGCC TTGGC
+
+AT
+AG
+";
+        let msa = read(&phy[..], Layout::Interleaved).unwrap();
+        assert_eq!(msa.seqs[0], b"AAGCTNGGGCAT".to_vec());
+        assert_eq!(msa.seqs[1], b"AAGCCTTGGCAG".to_vec());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let mut msa = Msa::new();
+        msa.push("a", b"ACGT".repeat(40));
+        msa.push("b", b"AC-T".repeat(40));
+        for &layout in &[Layout::Sequential, Layout::Interleaved] {
+            let mut out = Vec::new();
+            write(&msa, &mut out, layout).unwrap();
+            assert_eq!(read(&out[..], layout).unwrap(), msa);
+        }
+    }
+}
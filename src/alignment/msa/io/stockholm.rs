@@ -0,0 +1,160 @@
+//! Stockholm 1.0 format, as used by Pfam and Rfam.
+//!
+//! Sequence lines (`name residues`) may be split over several blocks. Per-column
+//! `#=GC <feature> <annotation>` lines are kept in
+//! [Msa::column_annotations](../../struct.Msa.html#structfield.column_annotations);
+//! other markup (`#=GF`, `#=GS`, `#=GR`) is skipped. Only the first alignment of a
+//! file is read.
+//!
+//! # References
+//!
+//! - [Stockholm format specification](https://sonnhammer.sbc.su.se/Stockholm.html)
+
+use super::{invalid_data, msa_from_rows, name_width};
+use crate::alignment::msa::Msa;
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+pub fn read<R: BufRead>(reader: R) -> io::Result<Msa> {
+    let mut lines = reader.lines();
+    match lines.next() {
+        Some(line) => {
+            if !line?.starts_with("# STOCKHOLM") {
+                return Err(invalid_data("missing '# STOCKHOLM 1.0' header"));
+            }
+        }
+        None => return Err(invalid_data("empty input")),
+    }
+    let mut rows: Vec<(String, Vec<u8>)> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut column_annotations: Vec<(String, Vec<u8>)> = Vec::new();
+    let mut terminated = false;
+    for line in lines {
+        let line = line?;
+        let line = line.trim_end();
+        if line == "//" {
+            terminated = true;
+            break;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(gc) = line.strip_prefix("#=GC") {
+            let mut fields = gc.trim_start().splitn(2, char::is_whitespace);
+            let feature = fields.next().unwrap();
+            let annotation = fields
+                .next()
+                .map(str::trim)
+                .filter(|a| !a.is_empty())
+                .ok_or_else(|| invalid_data(format!("empty #=GC {} line", feature)))?;
+            match column_annotations.iter_mut().find(|(f, _)| f == feature) {
+                Some((_, a)) => a.extend_from_slice(annotation.as_bytes()),
+                None => {
+                    column_annotations.push((feature.to_owned(), annotation.as_bytes().to_vec()))
+                }
+            }
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let name = fields.next().unwrap();
+        let residues = fields
+            .next()
+            .ok_or_else(|| invalid_data(format!("no residues for sequence '{}'", name)))?;
+        let i = *index.entry(name.to_owned()).or_insert_with(|| {
+            rows.push((name.to_owned(), Vec::new()));
+            rows.len() - 1
+        });
+        rows[i].1.extend_from_slice(residues.as_bytes());
+    }
+    if !terminated {
+        return Err(invalid_data("missing '//' terminator"));
+    }
+    let mut msa = msa_from_rows(rows)?;
+    for (feature, annotation) in column_annotations {
+        if annotation.len() != msa.ncols() {
+            return Err(invalid_data(format!(
+                "#=GC {} has length {}, expected {}",
+                feature,
+                annotation.len(),
+                msa.ncols()
+            )));
+        }
+        msa.column_annotations.insert(feature, annotation);
+    }
+    Ok(msa)
+}
+
+/// Write the alignment in Pfam style, i.e. one unwrapped line per sequence.
+pub fn write<W: Write>(msa: &Msa, mut writer: W) -> io::Result<()> {
+    writeln!(writer, "# STOCKHOLM 1.0")?;
+    writeln!(writer)?;
+    let gc_width = msa
+        .column_annotations
+        .keys()
+        .map(|k| k.len() + 6)
+        .max()
+        .unwrap_or(0);
+    let width = name_width(msa, 1).max(gc_width);
+    for (name, seq) in msa.iter() {
+        writeln!(
+            writer,
+            "{:width$}{}",
+            name,
+            String::from_utf8_lossy(seq),
+            width = width
+        )?;
+    }
+    for (feature, annotation) in msa.column_annotations.iter() {
+        writeln!(
+            writer,
+            "{:width$}{}",
+            format!("#=GC {}", feature),
+            String::from_utf8_lossy(annotation),
+            width = width
+        )?;
+    }
+    writeln!(writer, "//")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static STO: &[u8] = b"# STOCKHOLM 1.0
+#=GF ID    Example
+
+#=GS seq1/1-6 AC P00001
+seq1/1-6     ACD.EF
+seq2/3-9     ACDGEF
+#=GR seq1/1-6 SS HHH.HH
+#=GC SS_cons HHH.HH
+
+seq1/1-6     GH
+seq2/3-9     G-
+#=GC SS_cons EE
+//
+";
+
+    #[test]
+    fn test_read() {
+        let msa = read(STO).unwrap();
+        assert_eq!(msa.names, vec!["seq1/1-6", "seq2/3-9"]);
+        assert_eq!(msa.seqs[0], b"ACD.EFGH".to_vec());
+        assert_eq!(msa.seqs[1], b"ACDGEFG-".to_vec());
+        assert_eq!(
+            msa.column_annotations.get("SS_cons"),
+            Some(&b"HHH.HHEE".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let msa = read(STO).unwrap();
+        let mut out = Vec::new();
+        write(&msa, &mut out).unwrap();
+        assert_eq!(read(&out[..]).unwrap(), msa);
+    }
+}
@@ -0,0 +1,259 @@
+//! Column statistics of multiple alignments: gap fraction, Shannon entropy,
+//! sum-of-pairs score, consensus sequences and trimming of unreliable columns.
+//!
+//! Residues are compared case-insensitively; `-` and `.` are gaps.
+//!
+//! # Example
+//!
+//! ```
+//! use bioinformatics_algorithms::alignment::msa::{stats, Msa};
+//! let mut msa = Msa::new();
+//! msa.push("a", b"ACGT-A".to_vec());
+//! msa.push("b", b"ACGA--".to_vec());
+//! msa.push("c", b"ACGA-G".to_vec());
+//! assert_eq!(stats::majority_consensus(&msa, 0.5, b'N'), b"ACGA-N".to_vec());
+//! assert_eq!(stats::iupac_consensus(&msa), b"ACGW-R".to_vec());
+//! let trimmed = stats::trim(&msa, &stats::TrimParams::default());
+//! assert_eq!(trimmed.seqs[0], b"ACGTA".to_vec());
+//! ```
+//!
+//! # References
+//!
+//! - [Capella-Gutiérrez S., Silla-Martínez J.M., Gabaldón T. (2009) trimAl: a tool for automated alignment trimming in large-scale phylogenetic analyses. _Bioinformatics_ **25**: 1972-1973.](https://doi.org/10.1093/bioinformatics/btp348)
+
+use crate::alignment::msa::{is_gap, Msa, GAP};
+use crate::alignment::{MatchFunc, Score};
+use std::collections::BTreeMap;
+
+/// Fraction of gaps in a column
+pub fn gap_fraction(col: &[u8]) -> f32 {
+    if col.is_empty() {
+        return 0.0;
+    }
+    col.iter().filter(|&&c| is_gap(c)).count() as f32 / col.len() as f32
+}
+
+/// Shannon entropy (in bits) of the residue distribution of a column. Gaps are
+/// ignored; a column without residues has zero entropy.
+pub fn column_entropy(col: &[u8]) -> f32 {
+    let counts = residue_counts(col);
+    let total: u32 = counts.values().sum();
+    counts
+        .values()
+        .map(|&n| {
+            let p = n as f32 / total as f32;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Sum-of-pairs score of a column: every pair of residues is scored with
+/// `match_fn`, every residue–gap pair with `gap_score`, and gap–gap pairs score 0.
+pub fn column_sum_of_pairs<F: MatchFunc>(col: &[u8], match_fn: &F, gap_score: Score) -> Score {
+    let mut score = 0;
+    for (i, &a) in col.iter().enumerate() {
+        for &b in &col[i + 1..] {
+            score += match (is_gap(a), is_gap(b)) {
+                (false, false) => match_fn.score(a, b),
+                (true, true) => 0,
+                _ => gap_score,
+            };
+        }
+    }
+    score
+}
+
+pub fn gap_fractions(msa: &Msa) -> Vec<f32> {
+    (0..msa.ncols())
+        .map(|j| gap_fraction(&msa.column(j)))
+        .collect()
+}
+
+pub fn entropies(msa: &Msa) -> Vec<f32> {
+    (0..msa.ncols())
+        .map(|j| column_entropy(&msa.column(j)))
+        .collect()
+}
+
+pub fn sum_of_pairs_scores<F: MatchFunc>(msa: &Msa, match_fn: &F, gap_score: Score) -> Vec<Score> {
+    (0..msa.ncols())
+        .map(|j| column_sum_of_pairs(&msa.column(j), match_fn, gap_score))
+        .collect()
+}
+
+/// Sum-of-pairs score of the whole alignment
+pub fn sum_of_pairs<F: MatchFunc>(msa: &Msa, match_fn: &F, gap_score: Score) -> Score {
+    sum_of_pairs_scores(msa, match_fn, gap_score).iter().sum()
+}
+
+/// Majority-rule consensus. For each column, the most frequent symbol (gaps
+/// included) is reported if its frequency is at least `min_frequency`;
+/// otherwise `ambiguous` (e.g. `b'N'` or `b'X'`) is reported. Ties are broken in
+/// favour of the smallest byte.
+pub fn majority_consensus(msa: &Msa, min_frequency: f32, ambiguous: u8) -> Vec<u8> {
+    (0..msa.ncols())
+        .map(|j| {
+            let col = msa.column(j);
+            let mut counts: BTreeMap<u8, u32> = BTreeMap::new();
+            for &c in col.iter() {
+                let c = if is_gap(c) {
+                    GAP
+                } else {
+                    c.to_ascii_uppercase()
+                };
+                *counts.entry(c).or_insert(0) += 1;
+            }
+            let (&best, &n) = counts
+                .iter()
+                .fold(None, |best: Option<(&u8, &u32)>, (c, n)| match best {
+                    Some((_, m)) if m >= n => best,
+                    _ => Some((c, n)),
+                })
+                .unwrap_or((&GAP, &0));
+            if !col.is_empty() && n as f32 / col.len() as f32 >= min_frequency {
+                best
+            } else {
+                ambiguous
+            }
+        })
+        .collect()
+}
+
+/// Nucleotide consensus using IUPAC ambiguity codes for the set of bases present
+/// in each column (gaps ignored; ambiguity codes in the input are expanded, and
+/// `U` is treated as `T`). Columns consisting only of gaps yield a gap.
+pub fn iupac_consensus(msa: &Msa) -> Vec<u8> {
+    (0..msa.ncols())
+        .map(|j| {
+            let mask = msa
+                .column(j)
+                .iter()
+                .fold(0u8, |mask, &c| mask | iupac_to_mask(c));
+            IUPAC_CODES[mask as usize]
+        })
+        .collect()
+}
+
+/// IUPAC code indexed by a bit mask of A (1), C (2), G (4) and T (8)
+const IUPAC_CODES: [u8; 16] = *b"-ACMGRSVTWYHKDBN";
+
+fn iupac_to_mask(c: u8) -> u8 {
+    match c.to_ascii_uppercase() {
+        b'A' => 0b0001,
+        b'C' => 0b0010,
+        b'G' => 0b0100,
+        b'T' | b'U' => 0b1000,
+        b'M' => 0b0011,
+        b'R' => 0b0101,
+        b'W' => 0b1001,
+        b'S' => 0b0110,
+        b'Y' => 0b1010,
+        b'K' => 0b1100,
+        b'V' => 0b0111,
+        b'H' => 0b1011,
+        b'D' => 0b1101,
+        b'B' => 0b1110,
+        b'N' => 0b1111,
+        _ => 0,
+    }
+}
+
+fn residue_counts(col: &[u8]) -> BTreeMap<u8, u32> {
+    let mut counts = BTreeMap::new();
+    for &c in col.iter().filter(|&&c| !is_gap(c)) {
+        *counts.entry(c.to_ascii_uppercase()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Thresholds used by [trim](fn.trim.html).
+#[derive(Debug, Clone)]
+pub struct TrimParams {
+    /// Columns with a larger fraction of gaps are removed (cf. trimAl's `-gt`,
+    /// which gives the minimum fraction of residues instead)
+    pub max_gap_fraction: f32,
+    /// Columns with a larger Shannon entropy (in bits) are removed
+    pub max_entropy: f32,
+    /// Minimum fraction of the columns to keep (trimAl's `-cons`). If the
+    /// thresholds above remove too many columns, the best of the removed ones
+    /// (fewest gaps first, then lowest entropy) are restored.
+    pub min_kept_fraction: f32,
+}
+
+impl Default for TrimParams {
+    /// Remove columns with more than half gaps, regardless of conservation
+    fn default() -> Self {
+        TrimParams {
+            max_gap_fraction: 0.5,
+            max_entropy: f32::INFINITY,
+            min_kept_fraction: 0.0,
+        }
+    }
+}
+
+/// Decide for each column whether it is kept by trimming with `params`.
+pub fn trim_mask(msa: &Msa, params: &TrimParams) -> Vec<bool> {
+    let gaps = gap_fractions(msa);
+    let entropies = entropies(msa);
+    let mut keep: Vec<bool> = gaps
+        .iter()
+        .zip(entropies.iter())
+        .map(|(&g, &h)| g <= params.max_gap_fraction && h <= params.max_entropy)
+        .collect();
+    let min_kept = (params.min_kept_fraction * msa.ncols() as f32).ceil() as usize;
+    let kept = keep.iter().filter(|&&k| k).count();
+    if kept < min_kept {
+        let mut rejected: Vec<usize> = (0..msa.ncols()).filter(|&j| !keep[j]).collect();
+        rejected.sort_by(|&a, &b| {
+            gaps[a]
+                .partial_cmp(&gaps[b])
+                .unwrap()
+                .then(entropies[a].partial_cmp(&entropies[b]).unwrap())
+                .then(a.cmp(&b))
+        });
+        for &j in rejected.iter().take(min_kept - kept) {
+            keep[j] = true;
+        }
+    }
+    keep
+}
+
+/// Remove gappy and poorly conserved columns, trimAl-style.
+pub fn trim(msa: &Msa, params: &TrimParams) -> Msa {
+    msa.select_columns(&trim_mask(msa, params))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alignment::MatchParams;
+
+    #[test]
+    fn test_column_entropy() {
+        assert_eq!(column_entropy(b"AAAA"), 0.0);
+        assert!((column_entropy(b"ACGT") - 2.0).abs() < 1e-6);
+        assert!((column_entropy(b"AC--") - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_column_sum_of_pairs() {
+        let match_fn = MatchParams::new(1, -1);
+        // A-A: 1, A-C: -1 (x2), residue-gap: -2 (x3)
+        assert_eq!(
+            column_sum_of_pairs(b"AAC-", &match_fn, -2),
+            1 - 1 - 1 - 2 - 2 - 2
+        );
+    }
+
+    #[test]
+    fn test_trim_min_kept_fraction() {
+        let mut msa = Msa::new();
+        msa.push("a", b"A--C".to_vec());
+        msa.push("b", b"A-GC".to_vec());
+        msa.push("c", b"A--C".to_vec());
+        let mut params = TrimParams::default();
+        assert_eq!(trim_mask(&msa, &params), vec![true, false, false, true]);
+        params.min_kept_fraction = 0.75;
+        assert_eq!(trim_mask(&msa, &params), vec![true, false, true, true]);
+    }
+}
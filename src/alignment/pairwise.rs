@@ -1,2 +1,3 @@
 pub mod gotoh_space_efficient;
 pub mod nw_se;
+pub mod pair_hmm;
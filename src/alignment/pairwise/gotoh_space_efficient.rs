@@ -18,8 +18,10 @@
 //! use bioinformatics_algorithms::alignment::Scoring;
 //! let x = b"ATGATGATG";
 //! let y = b"ATGAATG";
-//! let mut aligner = GotohSpaceEfficientAligner::new(Scoring::from_scores(-5, -1, 2, -1));
+//! let scoring = Scoring::from_scores(-5, -1, 2, -1);
+//! let aligner = GotohSpaceEfficientAligner::new(&scoring);
 //! let res = aligner.global(x, y);
+//! assert_eq!(res.score, 7);
 //! ```
 //!
 //! # References
@@ -50,7 +52,7 @@ impl<'s, F: MatchFunc> GotohSpaceEfficientAligner<'s, F> {
         );
         let score = self.cost_only(x, y, false, self.scoring.gap_open).0[y.len()];
         return AlignmentResult {
-            operations,
+            alignment: operations,
             score,
             x,
             y,
@@ -0,0 +1,378 @@
+//! Pair hidden Markov model for probabilistic pairwise alignment.
+//!
+//! The model has a match state `M` emitting an aligned pair of residues `(x_i, y_j)`
+//! and two gap states: `X` emits `x_i` against a gap (a deletion) and `Y` emits
+//! `y_j` against a gap (an insertion). With gap-open probability `δ` and
+//! gap-extend probability `ε` the transitions are:
+//!
+//! ```text
+//! M → M: 1 - 2δ    M → X: δ    M → Y: δ
+//! X → M: 1 - ε     X → X: ε
+//! Y → M: 1 - ε     Y → Y: ε
+//! ```
+//!
+//! The begin state behaves like `M`. In the match state identical residues are
+//! emitted with total probability `match_prob` and different ones share the rest;
+//! the gap states emit uniformly over an alphabet of `alphabet_size` residues.
+//! Everything is computed in log space.
+//!
+//! Besides the most probable (Viterbi) alignment, the full likelihood `P(x, y)`
+//! summed over all alignments is available via the Forward algorithm, e.g. as a
+//! read likelihood given a candidate haplotype, and posterior match probabilities
+//! `P(x_i ◇ y_j | x, y)` give the confidence of each aligned pair and the maximum
+//! expected accuracy (MEA) alignment.
+//!
+//! # Example
+//!
+//! ```
+//! use bioinformatics_algorithms::alignment::pairwise::pair_hmm::PairHmm;
+//! use bioinformatics_algorithms::alignment::AlignmentOperation::*;
+//! let hmm = PairHmm::new(0.05, 0.4, 0.9, 4);
+//! let (_, ops) = hmm.viterbi(b"ACGTTA", b"ACGTA");
+//! assert_eq!(ops.iter().filter(|&&op| op == Del).count(), 1);
+//! let ref_likelihood = hmm.forward(b"ACGTTA", b"ACGTTA");
+//! let alt_likelihood = hmm.forward(b"ACGTTA", b"ACCTTA");
+//! assert!(ref_likelihood > alt_likelihood);
+//! ```
+//!
+//! # References
+//!
+//! - Durbin R., Eddy S.R., Krogh A., Mitchison G. (1998) _Biological Sequence Analysis:
+//!   Probabilistic Models of Proteins and Nucleic Acids_, Cambridge University Press. Chapter 4.
+//! - [Do C.B., Mahabhashyam M.S.P., Brudno M., Batzoglou S. (2005) ProbCons: Probabilistic consistency-based multiple sequence alignment. _Genome Res._ **15**: 330-340.](https://doi.org/10.1101/gr.2821705)
+
+use crate::alignment::profile_hmm::log_sum_exp;
+use crate::alignment::{AlignmentOperation, Seq};
+
+const NEG_INF: f64 = f64::NEG_INFINITY;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum State {
+    Match,
+    X,
+    Y,
+}
+
+#[derive(Debug, Clone)]
+pub struct PairHmm {
+    pub gap_open: f64,
+    pub gap_extend: f64,
+    pub match_prob: f64,
+    pub alphabet_size: usize,
+}
+
+/// Log-space DP matrices, indexed by `i` (`0..=m`) and then `j` (`0..=n`)
+struct DpMatrices {
+    m: Vec<Vec<f64>>,
+    x: Vec<Vec<f64>>,
+    y: Vec<Vec<f64>>,
+}
+
+impl DpMatrices {
+    fn new(m: usize, n: usize) -> Self {
+        DpMatrices {
+            m: vec![vec![NEG_INF; n + 1]; m + 1],
+            x: vec![vec![NEG_INF; n + 1]; m + 1],
+            y: vec![vec![NEG_INF; n + 1]; m + 1],
+        }
+    }
+}
+
+impl PairHmm {
+    /// Create a new PairHmm with the given parameters.
+    ///
+    /// # Arguments
+    ///
+    /// * `gap_open` - probability `δ` of moving from the match state to a gap state (less than 0.5)
+    /// * `gap_extend` - probability `ε` of staying in a gap state (less than 1)
+    /// * `match_prob` - probability that the match state emits two identical residues
+    /// * `alphabet_size` - number of distinct residues, e.g. 4 for DNA or 20 for proteins
+    pub fn new(gap_open: f64, gap_extend: f64, match_prob: f64, alphabet_size: usize) -> Self {
+        assert!(
+            gap_open > 0.0 && gap_open < 0.5,
+            "gap_open must be in (0, 0.5)"
+        );
+        assert!(
+            (0.0..1.0).contains(&gap_extend),
+            "gap_extend must be in [0, 1)"
+        );
+        assert!(
+            match_prob > 0.0 && match_prob <= 1.0,
+            "match_prob must be in (0, 1]"
+        );
+        assert!(alphabet_size >= 2, "alphabet_size must be at least 2");
+        PairHmm {
+            gap_open,
+            gap_extend,
+            match_prob,
+            alphabet_size,
+        }
+    }
+
+    /// Log-probability of the match state emitting `a` and `b`
+    #[inline]
+    fn emit_pair(&self, a: u8, b: u8) -> f64 {
+        let k = self.alphabet_size as f64;
+        if a.eq_ignore_ascii_case(&b) {
+            (self.match_prob / k).ln()
+        } else {
+            ((1.0 - self.match_prob) / (k * (k - 1.0))).ln()
+        }
+    }
+
+    /// Log-probability of a gap state emitting one residue
+    #[inline]
+    fn emit_single(&self) -> f64 {
+        -(self.alphabet_size as f64).ln()
+    }
+
+    /// Log-probabilities of M→M, M→gap, gap→M and gap→gap
+    #[inline]
+    fn transitions(&self) -> (f64, f64, f64, f64) {
+        (
+            (1.0 - 2.0 * self.gap_open).ln(),
+            self.gap_open.ln(),
+            (1.0 - self.gap_extend).ln(),
+            self.gap_extend.ln(),
+        )
+    }
+
+    /// Most probable alignment of `x` and `y`.
+    ///
+    /// Returns the log-probability of the alignment and its operations, where `Del`
+    /// consumes a residue of `x` only and `Ins` a residue of `y` only.
+    pub fn viterbi(&self, x: &Seq, y: &Seq) -> (f64, Vec<AlignmentOperation>) {
+        let (mm, mg, gm, gg) = self.transitions();
+        let q = self.emit_single();
+        let (m, n) = (x.len(), y.len());
+        let mut v = DpMatrices::new(m, n);
+        let mut tm = vec![vec![State::Match; n + 1]; m + 1];
+        let mut tx = vec![vec![State::Match; n + 1]; m + 1];
+        let mut ty = vec![vec![State::Match; n + 1]; m + 1];
+        v.m[0][0] = 0.0;
+        for i in 0..=m {
+            for j in 0..=n {
+                if i > 0 && j > 0 {
+                    let (s, from) = max3(
+                        v.m[i - 1][j - 1] + mm,
+                        v.x[i - 1][j - 1] + gm,
+                        v.y[i - 1][j - 1] + gm,
+                    );
+                    v.m[i][j] = s + self.emit_pair(x[i - 1], y[j - 1]);
+                    tm[i][j] = from;
+                }
+                if i > 0 {
+                    let (s, from) = max3(v.m[i - 1][j] + mg, v.x[i - 1][j] + gg, NEG_INF);
+                    v.x[i][j] = s + q;
+                    tx[i][j] = from;
+                }
+                if j > 0 {
+                    let (s, from) = max3(v.m[i][j - 1] + mg, NEG_INF, v.y[i][j - 1] + gg);
+                    v.y[i][j] = s + q;
+                    ty[i][j] = from;
+                }
+            }
+        }
+        let (score, mut state) = max3(v.m[m][n], v.x[m][n], v.y[m][n]);
+        let mut operations = Vec::with_capacity(m + n);
+        let (mut i, mut j) = (m, n);
+        while i > 0 || j > 0 {
+            match state {
+                State::Match => {
+                    operations.push(if x[i - 1].eq_ignore_ascii_case(&y[j - 1]) {
+                        AlignmentOperation::Match
+                    } else {
+                        AlignmentOperation::Subst
+                    });
+                    state = tm[i][j];
+                    i -= 1;
+                    j -= 1;
+                }
+                State::X => {
+                    operations.push(AlignmentOperation::Del);
+                    state = tx[i][j];
+                    i -= 1;
+                }
+                State::Y => {
+                    operations.push(AlignmentOperation::Ins);
+                    state = ty[i][j];
+                    j -= 1;
+                }
+            }
+        }
+        operations.reverse();
+        (score, operations)
+    }
+
+    /// Log-likelihood `ln P(x, y)` summed over all alignments (Forward algorithm).
+    pub fn forward(&self, x: &Seq, y: &Seq) -> f64 {
+        let f = self.forward_matrices(x, y);
+        let (m, n) = (x.len(), y.len());
+        log_sum_exp(&[f.m[m][n], f.x[m][n], f.y[m][n]])
+    }
+
+    /// Log-likelihood computed with the Backward algorithm. Equal to
+    /// [forward](#method.forward) up to rounding errors.
+    pub fn backward(&self, x: &Seq, y: &Seq) -> f64 {
+        self.backward_matrices(x, y).m[0][0]
+    }
+
+    /// Posterior probabilities `P(x_i ◇ y_j | x, y)` that `x[i]` is aligned to `y[j]`,
+    /// indexed by `i` (`0..m`) and then `j` (`0..n`).
+    pub fn posterior_match_probabilities(&self, x: &Seq, y: &Seq) -> Vec<Vec<f64>> {
+        let f = self.forward_matrices(x, y);
+        let b = self.backward_matrices(x, y);
+        let total = b.m[0][0];
+        (1..=x.len())
+            .map(|i| {
+                (1..=y.len())
+                    .map(|j| (f.m[i][j] + b.m[i][j] - total).exp())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Maximum expected accuracy alignment: the alignment maximising the sum of the
+    /// posterior probabilities of its aligned pairs. Returns that expected number of
+    /// correctly aligned pairs and the alignment operations.
+    pub fn mea_alignment(&self, x: &Seq, y: &Seq) -> (f64, Vec<AlignmentOperation>) {
+        let post = self.posterior_match_probabilities(x, y);
+        let (m, n) = (x.len(), y.len());
+        let mut a = vec![vec![0f64; n + 1]; m + 1];
+        for i in 1..=m {
+            for j in 1..=n {
+                a[i][j] = (a[i - 1][j - 1] + post[i - 1][j - 1])
+                    .max(a[i - 1][j])
+                    .max(a[i][j - 1]);
+            }
+        }
+        let mut operations = Vec::with_capacity(m + n);
+        let (mut i, mut j) = (m, n);
+        while i > 0 || j > 0 {
+            if i > 0 && j > 0 && a[i][j] == a[i - 1][j - 1] + post[i - 1][j - 1] {
+                operations.push(if x[i - 1].eq_ignore_ascii_case(&y[j - 1]) {
+                    AlignmentOperation::Match
+                } else {
+                    AlignmentOperation::Subst
+                });
+                i -= 1;
+                j -= 1;
+            } else if i > 0 && (j == 0 || a[i][j] == a[i - 1][j]) {
+                operations.push(AlignmentOperation::Del);
+                i -= 1;
+            } else {
+                operations.push(AlignmentOperation::Ins);
+                j -= 1;
+            }
+        }
+        operations.reverse();
+        (a[m][n], operations)
+    }
+
+    fn forward_matrices(&self, x: &Seq, y: &Seq) -> DpMatrices {
+        let (mm, mg, gm, gg) = self.transitions();
+        let q = self.emit_single();
+        let (m, n) = (x.len(), y.len());
+        let mut f = DpMatrices::new(m, n);
+        f.m[0][0] = 0.0;
+        for i in 0..=m {
+            for j in 0..=n {
+                if i > 0 && j > 0 {
+                    f.m[i][j] = self.emit_pair(x[i - 1], y[j - 1])
+                        + log_sum_exp(&[
+                            f.m[i - 1][j - 1] + mm,
+                            f.x[i - 1][j - 1] + gm,
+                            f.y[i - 1][j - 1] + gm,
+                        ]);
+                }
+                if i > 0 {
+                    f.x[i][j] = q + log_sum_exp(&[f.m[i - 1][j] + mg, f.x[i - 1][j] + gg]);
+                }
+                if j > 0 {
+                    f.y[i][j] = q + log_sum_exp(&[f.m[i][j - 1] + mg, f.y[i][j - 1] + gg]);
+                }
+            }
+        }
+        f
+    }
+
+    fn backward_matrices(&self, x: &Seq, y: &Seq) -> DpMatrices {
+        let (mm, mg, gm, gg) = self.transitions();
+        let q = self.emit_single();
+        let (m, n) = (x.len(), y.len());
+        let mut b = DpMatrices::new(m, n);
+        for i in (0..=m).rev() {
+            for j in (0..=n).rev() {
+                if i == m && j == n {
+                    b.m[i][j] = 0.0;
+                    b.x[i][j] = 0.0;
+                    b.y[i][j] = 0.0;
+                    continue;
+                }
+                let next_match = if i < m && j < n {
+                    self.emit_pair(x[i], y[j]) + b.m[i + 1][j + 1]
+                } else {
+                    NEG_INF
+                };
+                let next_x = if i < m { q + b.x[i + 1][j] } else { NEG_INF };
+                let next_y = if j < n { q + b.y[i][j + 1] } else { NEG_INF };
+                b.m[i][j] = log_sum_exp(&[mm + next_match, mg + next_x, mg + next_y]);
+                b.x[i][j] = log_sum_exp(&[gm + next_match, gg + next_x]);
+                b.y[i][j] = log_sum_exp(&[gm + next_match, gg + next_y]);
+            }
+        }
+        b
+    }
+}
+
+fn max3(m: f64, x: f64, y: f64) -> (f64, State) {
+    let mut res = (m, State::Match);
+    if x > res.0 {
+        res = (x, State::X);
+    }
+    if y > res.0 {
+        res = (y, State::Y);
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use AlignmentOperation::{Del, Ins, Match, Subst};
+
+    #[test]
+    fn test_viterbi() {
+        let hmm = PairHmm::new(0.05, 0.4, 0.9, 4);
+        assert_eq!(hmm.viterbi(b"ACGT", b"ACGT").1, vec![Match; 4]);
+        assert_eq!(
+            hmm.viterbi(b"ACGT", b"AGGT").1,
+            vec![Match, Subst, Match, Match]
+        );
+        assert_eq!(hmm.viterbi(b"ACG", b"").1, vec![Del; 3]);
+        assert_eq!(hmm.viterbi(b"", b"AC").1, vec![Ins; 2]);
+    }
+
+    #[test]
+    fn test_forward_backward() {
+        let hmm = PairHmm::new(0.1, 0.3, 0.8, 4);
+        let (x, y) = (b"ACGTTGCA", b"ACTTGGCA");
+        let f = hmm.forward(x, y);
+        assert!((f - hmm.backward(x, y)).abs() < 1e-9);
+        assert!(f >= hmm.viterbi(x, y).0);
+    }
+
+    #[test]
+    fn test_posterior() {
+        let hmm = PairHmm::new(0.1, 0.3, 0.8, 4);
+        let (x, y) = (b"GATTACA", b"GATACA");
+        for row in hmm.posterior_match_probabilities(x, y) {
+            let total: f64 = row.iter().sum();
+            assert!(total <= 1.0 + 1e-9);
+        }
+        let (accuracy, ops) = hmm.mea_alignment(x, y);
+        assert!(accuracy > 4.0 && accuracy <= 6.0);
+        assert_eq!(ops.iter().filter(|&&op| op == Del).count(), 1);
+        assert_eq!(ops.iter().filter(|&&op| op == Match).count(), 6);
+    }
+}
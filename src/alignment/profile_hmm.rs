@@ -0,0 +1,568 @@
+//! Profile hidden Markov models (profile HMMs) with match, insert and delete states.
+//!
+//! The architecture follows HMMER's Plan7 core model: node `k` (`1..=M`) has a match
+//! state `M_k`, an insert state `I_k` and a delete state `D_k`; node 0 holds the
+//! begin state (stored as `M_0`) and the insert state `I_0`. The transitions out of
+//! node `k` are stored in the order of HMMER3 files:
+//! `M_k→M_k+1`, `M_k→I_k`, `M_k→D_k+1`, `I_k→M_k+1`, `I_k→I_k`, `D_k→M_k+1` and
+//! `D_k→D_k+1`, where `M_M+1` denotes the end state. All probabilities are stored as
+//! natural logarithms.
+//!
+//! Sequences are aligned globally to the model (from the begin to the end state).
+//! Alignments are given as [AlignmentOperation](../enum.AlignmentOperation.html)s
+//! in which `x` is the model's consensus sequence and `y` the aligned sequence:
+//! match states give `Match`/`Subst`, insert states `Ins` and delete states `Del`.
+//!
+//! Models can be built from a multiple alignment with
+//! [from_msa](struct.ProfileHmm.html#method.from_msa) or read from HMMER3 files with
+//! [hmmer::read](hmmer/fn.read.html).
+//!
+//! # Example
+//!
+//! ```
+//! use bioinformatics_algorithms::alignment::msa::Msa;
+//! use bioinformatics_algorithms::alignment::profile_hmm::ProfileHmm;
+//! use bioinformatics_algorithms::alignment::pssm::DNA;
+//! use bioinformatics_algorithms::alignment::AlignmentOperation::*;
+//! let mut msa = Msa::new();
+//! msa.push("a", b"AC-GT".to_vec());
+//! msa.push("b", b"ACAGT".to_vec());
+//! msa.push("c", b"AC-GA".to_vec());
+//! let hmm = ProfileHmm::from_msa(&msa, DNA, 0.5);
+//! assert_eq!(hmm.len(), 4);
+//! let (_, ops) = hmm.viterbi(b"ACGGT");
+//! assert_eq!(ops, vec![Match, Match, Ins, Match, Match]);
+//! assert!(hmm.forward(b"ACGGT") >= hmm.viterbi(b"ACGGT").0);
+//! ```
+//!
+//! # References
+//!
+//! - Durbin R., Eddy S.R., Krogh A., Mitchison G. (1998) _Biological Sequence Analysis:
+//!   Probabilistic Models of Proteins and Nucleic Acids_, Cambridge University Press. Chapter 5.
+//! - [Eddy S.R. (2011) Accelerated Profile HMM Searches. _PLoS Comput. Biol._ **7**: e1002195.](https://doi.org/10.1371/journal.pcbi.1002195)
+
+pub mod hmmer;
+
+use crate::alignment::msa::{is_gap, Msa};
+use crate::alignment::pssm::alphabet_lookup;
+use crate::alignment::{AlignmentOperation, Seq};
+
+pub const MM: usize = 0;
+pub const MI: usize = 1;
+pub const MD: usize = 2;
+pub const IM: usize = 3;
+pub const II: usize = 4;
+pub const DM: usize = 5;
+pub const DD: usize = 6;
+
+const NEG_INF: f64 = f64::NEG_INFINITY;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum State {
+    Match,
+    Insert,
+    Delete,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProfileHmm {
+    pub name: String,
+    pub alphabet: Vec<u8>,
+    lookup: [Option<usize>; 256],
+    /// Match emission log-probabilities, indexed by node and residue; node 0 is unused
+    pub match_emissions: Vec<Vec<f64>>,
+    /// Insert emission log-probabilities, indexed by node (`0..=M`) and residue
+    pub insert_emissions: Vec<Vec<f64>>,
+    /// Transition log-probabilities out of each node (`0..=M`), see [MM](constant.MM.html) etc.
+    pub transitions: Vec<[f64; 7]>,
+    /// Residue log-probabilities of the null model
+    pub background: Vec<f64>,
+    /// Most probable residue of each match state
+    pub consensus: Vec<u8>,
+}
+
+/// Forward or backward dynamic programming matrices, indexed by node and then by
+/// sequence position (`0..=n`)
+struct DpMatrices {
+    m: Vec<Vec<f64>>,
+    i: Vec<Vec<f64>>,
+    d: Vec<Vec<f64>>,
+}
+
+impl DpMatrices {
+    fn new(len: usize, n: usize) -> Self {
+        DpMatrices {
+            m: vec![vec![NEG_INF; n + 1]; len + 1],
+            i: vec![vec![NEG_INF; n + 1]; len + 1],
+            d: vec![vec![NEG_INF; n + 1]; len + 1],
+        }
+    }
+}
+
+impl ProfileHmm {
+    /// Assemble a model from log-probabilities. The consensus is derived from the
+    /// match emissions.
+    pub fn new(
+        name: String,
+        alphabet: Vec<u8>,
+        match_emissions: Vec<Vec<f64>>,
+        insert_emissions: Vec<Vec<f64>>,
+        transitions: Vec<[f64; 7]>,
+        background: Vec<f64>,
+    ) -> Self {
+        let len = transitions.len() - 1;
+        assert_eq!(match_emissions.len(), len + 1, "one row per node");
+        assert_eq!(insert_emissions.len(), len + 1, "one row per node");
+        let lookup = alphabet_lookup(&alphabet);
+        let consensus = match_emissions[1..]
+            .iter()
+            .map(|e| {
+                let best = (0..e.len())
+                    .max_by(|&a, &b| e[a].partial_cmp(&e[b]).unwrap())
+                    .unwrap();
+                alphabet[best]
+            })
+            .collect();
+        ProfileHmm {
+            name,
+            alphabet,
+            lookup,
+            match_emissions,
+            insert_emissions,
+            transitions,
+            background,
+            consensus,
+        }
+    }
+
+    /// Build a model from a multiple alignment.
+    ///
+    /// Columns in which the fraction of residues is at least `symfrac` become match
+    /// states; the others are insertions. Emission and transition counts are
+    /// collected from each sequence's path through the model and turned into
+    /// probabilities with Laplace (+1) pseudocounts. Insert states emit with the
+    /// background (uniform) distribution. Delete→insert and insert→delete
+    /// transitions, which Plan7 does not allow, are not counted.
+    pub fn from_msa(msa: &Msa, alphabet: &[u8], symfrac: f32) -> Self {
+        let n_res = alphabet.len();
+        let lookup = alphabet_lookup(alphabet);
+        let is_match: Vec<bool> = (0..msa.ncols())
+            .map(|j| {
+                let col = msa.column(j);
+                let residues = col.iter().filter(|&&c| !is_gap(c)).count();
+                !col.is_empty() && residues as f32 / col.len() as f32 >= symfrac
+            })
+            .collect();
+        let len = is_match.iter().filter(|&&m| m).count();
+
+        let mut match_counts = vec![vec![1.0f64; n_res]; len + 1];
+        let mut transition_counts = vec![[0.0f64; 7]; len + 1];
+        for seq in msa.seqs.iter() {
+            let mut prev = (State::Match, 0); // begin
+            let mut k = 0;
+            let mut path = Vec::with_capacity(seq.len() + 1);
+            for (&c, &m) in seq.iter().zip(is_match.iter()) {
+                if m {
+                    k += 1;
+                    if is_gap(c) {
+                        path.push((State::Delete, k));
+                    } else {
+                        if let Some(a) = lookup[c as usize] {
+                            match_counts[k][a] += 1.0;
+                        }
+                        path.push((State::Match, k));
+                    }
+                } else if !is_gap(c) {
+                    path.push((State::Insert, k));
+                }
+            }
+            path.push((State::Match, len + 1)); // end
+            for &next in path.iter() {
+                let t = match (prev.0, next.0) {
+                    (State::Match, State::Match) => Some(MM),
+                    (State::Match, State::Insert) => Some(MI),
+                    (State::Match, State::Delete) => Some(MD),
+                    (State::Insert, State::Match) => Some(IM),
+                    (State::Insert, State::Insert) => Some(II),
+                    (State::Delete, State::Match) => Some(DM),
+                    (State::Delete, State::Delete) => Some(DD),
+                    _ => None,
+                };
+                if let Some(t) = t {
+                    transition_counts[prev.1][t] += 1.0;
+                }
+                prev = next;
+            }
+        }
+
+        let match_emissions: Vec<Vec<f64>> = match_counts
+            .iter()
+            .enumerate()
+            .map(|(k, counts)| {
+                if k == 0 {
+                    return vec![NEG_INF; n_res];
+                }
+                let total: f64 = counts.iter().sum();
+                counts.iter().map(|&c| (c / total).ln()).collect()
+            })
+            .collect();
+        let background = vec![(1.0 / n_res as f64).ln(); n_res];
+        let insert_emissions = vec![background.clone(); len + 1];
+        let transitions = transition_counts
+            .iter()
+            .enumerate()
+            .map(|(k, counts)| {
+                let mut t = [NEG_INF; 7];
+                let groups: &[&[usize]] = if k == 0 {
+                    &[&[MM, MI, MD], &[IM, II]]
+                } else if k == len {
+                    &[&[MM, MI], &[IM, II], &[DM]]
+                } else {
+                    &[&[MM, MI, MD], &[IM, II], &[DM, DD]]
+                };
+                for group in groups.iter() {
+                    let total: f64 = group.iter().map(|&i| counts[i] + 1.0).sum();
+                    for &i in group.iter() {
+                        t[i] = ((counts[i] + 1.0) / total).ln();
+                    }
+                }
+                t
+            })
+            .collect();
+        Self::new(
+            String::new(),
+            alphabet.to_vec(),
+            match_emissions,
+            insert_emissions,
+            transitions,
+            background,
+        )
+    }
+
+    /// Number of nodes (match states), `M`
+    pub fn len(&self) -> usize {
+        self.transitions.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Residues outside the alphabet are emitted with probability `1 / K` by every
+    /// state, where `K` is the alphabet size.
+    #[inline]
+    fn emit(&self, emissions: &[f64], a: u8) -> f64 {
+        match self.lookup[a as usize] {
+            Some(k) => emissions[k],
+            None => -(self.alphabet.len() as f64).ln(),
+        }
+    }
+
+    /// Log-likelihood of `seq` under the null (background) model
+    pub fn null_log_likelihood(&self, seq: &Seq) -> f64 {
+        seq.iter().map(|&a| self.emit(&self.background, a)).sum()
+    }
+
+    /// Convert a log-likelihood of `seq` into a log-odds score in bits against the
+    /// null model.
+    pub fn bit_score(&self, log_likelihood: f64, seq: &Seq) -> f64 {
+        (log_likelihood - self.null_log_likelihood(seq)) / std::f64::consts::LN_2
+    }
+
+    /// Most probable path of `seq` through the model.
+    ///
+    /// Returns the log-probability of the path and the corresponding alignment of
+    /// the consensus (`x`) with `seq` (`y`).
+    pub fn viterbi(&self, seq: &Seq) -> (f64, Vec<AlignmentOperation>) {
+        let len = self.len();
+        let n = seq.len();
+        let mut v = DpMatrices::new(len, n);
+        // traceback: the state each cell was reached from
+        let mut tm = vec![vec![State::Match; n + 1]; len + 1];
+        let mut ti = vec![vec![State::Match; n + 1]; len + 1];
+        let mut td = vec![vec![State::Match; n + 1]; len + 1];
+        v.m[0][0] = 0.0;
+        for j in 0..=n {
+            for k in 0..=len {
+                if j > 0 {
+                    let a = seq[j - 1];
+                    if k > 0 {
+                        let t = &self.transitions[k - 1];
+                        let (s, from) = max3(
+                            v.m[k - 1][j - 1] + t[MM],
+                            v.i[k - 1][j - 1] + t[IM],
+                            v.d[k - 1][j - 1] + t[DM],
+                        );
+                        v.m[k][j] = s + self.emit(&self.match_emissions[k], a);
+                        tm[k][j] = from;
+                    }
+                    let t = &self.transitions[k];
+                    let (s, from) = max3(v.m[k][j - 1] + t[MI], v.i[k][j - 1] + t[II], NEG_INF);
+                    v.i[k][j] = s + self.emit(&self.insert_emissions[k], a);
+                    ti[k][j] = from;
+                }
+                if k > 0 {
+                    let t = &self.transitions[k - 1];
+                    let (s, from) = max3(v.m[k - 1][j] + t[MD], NEG_INF, v.d[k - 1][j] + t[DD]);
+                    v.d[k][j] = s;
+                    td[k][j] = from;
+                }
+            }
+        }
+        let t = &self.transitions[len];
+        let (score, mut state) = max3(
+            v.m[len][n] + t[MM],
+            v.i[len][n] + t[IM],
+            v.d[len][n] + t[DM],
+        );
+
+        if score == NEG_INF {
+            // `seq` can't be generated by the model
+            return (score, Vec::new());
+        }
+        let mut operations = Vec::with_capacity(len + n);
+        let (mut k, mut j) = (len, n);
+        while k > 0 || j > 0 {
+            match state {
+                State::Match => {
+                    operations.push(if self.consensus[k - 1].eq_ignore_ascii_case(&seq[j - 1]) {
+                        AlignmentOperation::Match
+                    } else {
+                        AlignmentOperation::Subst
+                    });
+                    state = tm[k][j];
+                    k -= 1;
+                    j -= 1;
+                }
+                State::Insert => {
+                    operations.push(AlignmentOperation::Ins);
+                    state = ti[k][j];
+                    j -= 1;
+                }
+                State::Delete => {
+                    operations.push(AlignmentOperation::Del);
+                    state = td[k][j];
+                    k -= 1;
+                }
+            }
+        }
+        operations.reverse();
+        (score, operations)
+    }
+
+    /// Log-likelihood of `seq`, summed over all paths (Forward algorithm).
+    pub fn forward(&self, seq: &Seq) -> f64 {
+        let f = self.forward_matrices(seq);
+        let len = self.len();
+        let n = seq.len();
+        let t = &self.transitions[len];
+        log_sum_exp(&[
+            f.m[len][n] + t[MM],
+            f.i[len][n] + t[IM],
+            f.d[len][n] + t[DM],
+        ])
+    }
+
+    /// Log-likelihood of `seq` computed with the Backward algorithm. Equal to
+    /// [forward](#method.forward) up to rounding errors.
+    pub fn backward(&self, seq: &Seq) -> f64 {
+        self.backward_matrices(seq).m[0][0]
+    }
+
+    /// Posterior probabilities that each residue of `seq` is emitted by each match
+    /// state, indexed by residue (`0..n`) and then by node (`1..=M`, with index 0
+    /// unused). The remaining probability of each residue belongs to insert states.
+    pub fn posterior_match_probabilities(&self, seq: &Seq) -> Vec<Vec<f64>> {
+        let f = self.forward_matrices(seq);
+        let b = self.backward_matrices(seq);
+        let total = b.m[0][0];
+        (1..=seq.len())
+            .map(|j| {
+                (0..=self.len())
+                    .map(|k| {
+                        if k == 0 {
+                            0.0
+                        } else {
+                            (f.m[k][j] + b.m[k][j] - total).exp()
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Posterior decoding: for each residue of `seq`, the node of the match state it
+    /// is most probably emitted by, or `None` if it is most probably inserted.
+    pub fn posterior_decoding(&self, seq: &Seq) -> Vec<Option<usize>> {
+        self.posterior_match_probabilities(seq)
+            .iter()
+            .map(|row| {
+                let (best, p) = row
+                    .iter()
+                    .enumerate()
+                    .skip(1)
+                    .fold(
+                        (0, 0.0),
+                        |best, (k, &p)| if p > best.1 { (k, p) } else { best },
+                    );
+                let p_insert = 1.0 - row.iter().sum::<f64>();
+                if best == 0 || p_insert > p {
+                    None
+                } else {
+                    Some(best)
+                }
+            })
+            .collect()
+    }
+
+    fn forward_matrices(&self, seq: &Seq) -> DpMatrices {
+        let len = self.len();
+        let n = seq.len();
+        let mut f = DpMatrices::new(len, n);
+        f.m[0][0] = 0.0;
+        for j in 0..=n {
+            for k in 0..=len {
+                if j > 0 {
+                    let a = seq[j - 1];
+                    if k > 0 {
+                        let t = &self.transitions[k - 1];
+                        f.m[k][j] = log_sum_exp(&[
+                            f.m[k - 1][j - 1] + t[MM],
+                            f.i[k - 1][j - 1] + t[IM],
+                            f.d[k - 1][j - 1] + t[DM],
+                        ]) + self.emit(&self.match_emissions[k], a);
+                    }
+                    let t = &self.transitions[k];
+                    f.i[k][j] = log_sum_exp(&[f.m[k][j - 1] + t[MI], f.i[k][j - 1] + t[II]])
+                        + self.emit(&self.insert_emissions[k], a);
+                }
+                if k > 0 {
+                    let t = &self.transitions[k - 1];
+                    f.d[k][j] = log_sum_exp(&[f.m[k - 1][j] + t[MD], f.d[k - 1][j] + t[DD]]);
+                }
+            }
+        }
+        f
+    }
+
+    fn backward_matrices(&self, seq: &Seq) -> DpMatrices {
+        let len = self.len();
+        let n = seq.len();
+        let mut b = DpMatrices::new(len, n);
+        for j in (0..=n).rev() {
+            for k in (0..=len).rev() {
+                let t = &self.transitions[k];
+                // log-probability of moving to the next match state (or the end)
+                // and emitting the rest of the sequence from there
+                let next_match = if k == len {
+                    if j == n {
+                        0.0
+                    } else {
+                        NEG_INF
+                    }
+                } else if j < n {
+                    self.emit(&self.match_emissions[k + 1], seq[j]) + b.m[k + 1][j + 1]
+                } else {
+                    NEG_INF
+                };
+                let next_insert = if j < n {
+                    self.emit(&self.insert_emissions[k], seq[j]) + b.i[k][j + 1]
+                } else {
+                    NEG_INF
+                };
+                let next_delete = if k < len { b.d[k + 1][j] } else { NEG_INF };
+                b.m[k][j] =
+                    log_sum_exp(&[t[MM] + next_match, t[MI] + next_insert, t[MD] + next_delete]);
+                b.i[k][j] = log_sum_exp(&[t[IM] + next_match, t[II] + next_insert]);
+                b.d[k][j] = log_sum_exp(&[t[DM] + next_match, t[DD] + next_delete]);
+            }
+        }
+        b
+    }
+}
+
+fn max3(m: f64, i: f64, d: f64) -> (f64, State) {
+    let mut res = (m, State::Match);
+    if i > res.0 {
+        res = (i, State::Insert);
+    }
+    if d > res.0 {
+        res = (d, State::Delete);
+    }
+    res
+}
+
+/// `ln(Σ exp(x))`, robust to large magnitudes and to all terms being `-∞`
+pub fn log_sum_exp(xs: &[f64]) -> f64 {
+    let max = xs.iter().cloned().fold(NEG_INF, f64::max);
+    if max == NEG_INF {
+        return NEG_INF;
+    }
+    max + xs.iter().map(|&x| (x - max).exp()).sum::<f64>().ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alignment::pssm::DNA;
+    use AlignmentOperation::{Del, Ins, Match};
+
+    fn toy_hmm() -> ProfileHmm {
+        let mut msa = Msa::new();
+        msa.push("a", b"ACG-TA".to_vec());
+        msa.push("b", b"ACGGTA".to_vec());
+        msa.push("c", b"A-G-TC".to_vec());
+        msa.push("d", b"ACG-TA".to_vec());
+        ProfileHmm::from_msa(&msa, DNA, 0.5)
+    }
+
+    #[test]
+    fn test_from_msa() {
+        let hmm = toy_hmm();
+        assert_eq!(hmm.len(), 5);
+        assert_eq!(hmm.consensus, b"ACGTA".to_vec());
+        for t in hmm.transitions.iter() {
+            for group in [&t[MM..=MD], &t[IM..=II], &t[DM..=DD]].iter() {
+                let total: f64 = group.iter().map(|p| p.exp()).sum();
+                assert!(total == 0.0 || (total - 1.0).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_viterbi() {
+        let hmm = toy_hmm();
+        assert_eq!(hmm.viterbi(b"ACGTA").1, vec![Match; 5]);
+        assert_eq!(
+            hmm.viterbi(b"AGTA").1,
+            vec![Match, Del, Match, Match, Match]
+        );
+        assert_eq!(
+            hmm.viterbi(b"ACGGTA").1,
+            vec![Match, Match, Match, Ins, Match, Match]
+        );
+    }
+
+    #[test]
+    fn test_forward_backward() {
+        let hmm = toy_hmm();
+        for seq in [&b"ACGTA"[..], b"AGTC", b"ACGGGTA", b"TTTT", b""].iter() {
+            let f = hmm.forward(seq);
+            let b = hmm.backward(seq);
+            assert!((f - b).abs() < 1e-9, "{} != {}", f, b);
+            assert!(f >= hmm.viterbi(seq).0);
+        }
+    }
+
+    #[test]
+    fn test_posterior_decoding() {
+        let hmm = toy_hmm();
+        assert_eq!(
+            hmm.posterior_decoding(b"ACGGTA"),
+            vec![Some(1), Some(2), Some(3), None, Some(4), Some(5)]
+        );
+        for row in hmm.posterior_match_probabilities(b"ACGGTA") {
+            assert!(row.iter().sum::<f64>() <= 1.0 + 1e-9);
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! Reader for HMMER3 ASCII profile files (`.hmm`), e.g. from Pfam.
+//!
+//! Probabilities are stored in these files as negative natural logarithms, with
+//! `*` for zero. Only the core model is read: the consensus is derived from the
+//! match emissions, and the null model is uniform over the alphabet given on the
+//! `HMM` line. Annotation columns after the match emissions (MAP, CONS, RF, MM, CS)
+//! and the statistical calibration lines are ignored.
+//!
+//! # References
+//!
+//! - [HMMER User's Guide, section "HMMER profile HMM files"](http://eddylab.org/software/hmmer/Userguide.pdf)
+
+use crate::alignment::msa::io::invalid_data;
+use crate::alignment::profile_hmm::ProfileHmm;
+use std::io::{self, BufRead};
+
+/// Read the first model of a HMMER3 file.
+pub fn read<R: BufRead>(reader: R) -> io::Result<ProfileHmm> {
+    let mut lines = reader.lines();
+    read_model(&mut lines)?.ok_or_else(|| invalid_data("no model found"))
+}
+
+/// Read all models of a (possibly concatenated) HMMER3 file, such as `Pfam-A.hmm`.
+pub fn read_all<R: BufRead>(reader: R) -> io::Result<Vec<ProfileHmm>> {
+    let mut lines = reader.lines();
+    let mut models = Vec::new();
+    while let Some(model) = read_model(&mut lines)? {
+        models.push(model);
+    }
+    Ok(models)
+}
+
+fn read_model<I: Iterator<Item = io::Result<String>>>(
+    lines: &mut I,
+) -> io::Result<Option<ProfileHmm>> {
+    // header section
+    let mut seen_format = false;
+    let mut name = String::new();
+    let mut leng = None;
+    let alphabet: Vec<u8> = loop {
+        let line = match lines.next() {
+            Some(line) => line?,
+            None if !seen_format => return Ok(None),
+            None => return Err(invalid_data("unexpected end of file in header")),
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        if !seen_format {
+            if !line.starts_with("HMMER3") {
+                return Err(invalid_data("not a HMMER3 file"));
+            }
+            seen_format = true;
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        match fields.next() {
+            Some("NAME") => name = fields.collect::<Vec<_>>().join(" "),
+            Some("LENG") => {
+                leng = Some(
+                    fields
+                        .next()
+                        .and_then(|f| f.parse::<usize>().ok())
+                        .ok_or_else(|| invalid_data("invalid LENG line"))?,
+                )
+            }
+            Some("HMM") => break fields.flat_map(|f| f.bytes()).collect(),
+            _ => {}
+        }
+    };
+    let k = alphabet.len();
+    if k == 0 {
+        return Err(invalid_data("empty alphabet on HMM line"));
+    }
+
+    // fields of the next non-blank line
+    let mut next_fields = || -> io::Result<Vec<String>> {
+        loop {
+            match lines.next() {
+                Some(line) => {
+                    let fields: Vec<String> = line?.split_whitespace().map(String::from).collect();
+                    if !fields.is_empty() {
+                        return Ok(fields);
+                    }
+                }
+                None => return Err(invalid_data("unexpected end of file in model")),
+            }
+        }
+    };
+    next_fields()?; // transition names: m->m m->i ...
+    let mut fields = next_fields()?;
+    if fields.first().map(String::as_str) == Some("COMPO") {
+        fields = next_fields()?;
+    }
+    let mut match_emissions = vec![vec![f64::NEG_INFINITY; k]];
+    let mut insert_emissions = vec![parse_probs(&fields, k)?];
+    let mut transitions = vec![parse_transitions(&next_fields()?)?];
+    loop {
+        let fields = next_fields()?;
+        if fields.first().map(String::as_str) == Some("//") {
+            break;
+        }
+        let node: usize = fields
+            .first()
+            .ok_or_else(|| invalid_data("empty node line"))?
+            .parse()
+            .map_err(|_| invalid_data(format!("invalid node line: {}", fields.join(" "))))?;
+        if node != transitions.len() {
+            return Err(invalid_data(format!(
+                "expected node {}, found {}",
+                transitions.len(),
+                node
+            )));
+        }
+        if fields.len() < k + 1 {
+            return Err(invalid_data(format!(
+                "too few match emissions at node {}",
+                node
+            )));
+        }
+        match_emissions.push(parse_probs(&fields[1..k + 1], k)?);
+        insert_emissions.push(parse_probs(&next_fields()?, k)?);
+        transitions.push(parse_transitions(&next_fields()?)?);
+    }
+    if let Some(leng) = leng {
+        if leng != transitions.len() - 1 {
+            return Err(invalid_data(format!(
+                "LENG is {} but the model has {} nodes",
+                leng,
+                transitions.len() - 1
+            )));
+        }
+    }
+    let background = vec![-(k as f64).ln(); k];
+    Ok(Some(ProfileHmm::new(
+        name,
+        alphabet,
+        match_emissions,
+        insert_emissions,
+        transitions,
+        background,
+    )))
+}
+
+/// Convert a `-ln(p)` field (or `*` for zero) to `ln(p)`
+fn parse_prob(field: &str) -> io::Result<f64> {
+    if field == "*" {
+        return Ok(f64::NEG_INFINITY);
+    }
+    field
+        .parse::<f64>()
+        .map(|x| -x)
+        .map_err(|_| invalid_data(format!("invalid probability '{}'", field)))
+}
+
+fn parse_probs<S: AsRef<str>>(fields: &[S], k: usize) -> io::Result<Vec<f64>> {
+    if fields.len() < k {
+        return Err(invalid_data("too few emission probabilities"));
+    }
+    fields[..k].iter().map(|f| parse_prob(f.as_ref())).collect()
+}
+
+fn parse_transitions(fields: &[String]) -> io::Result<[f64; 7]> {
+    if fields.len() < 7 {
+        return Err(invalid_data("too few transition probabilities"));
+    }
+    let mut t = [0.0; 7];
+    for (t, f) in t.iter_mut().zip(fields.iter()) {
+        *t = parse_prob(f)?;
+    }
+    Ok(t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alignment::AlignmentOperation::*;
+
+    static HMM: &[u8] = b"HMMER3/f [3.1b2 | February 2015]
+NAME  toy
+LENG  2
+ALPH  DNA
+RF    no
+STATS LOCAL MSV      -5.0  0.7
+HMM          A        C        G        T
+            m->m     m->i     m->d     i->m     i->i     d->m     d->d
+  COMPO   1.38629  1.38629  1.38629  1.38629
+          1.38629  1.38629  1.38629  1.38629
+          0.10536  2.99573  2.99573  0.69315  0.69315  0.00000        *
+      1   0.10536  3.40120  3.40120  3.40120      1 a - - -
+          1.38629  1.38629  1.38629  1.38629
+          0.10536  2.99573  2.99573  0.69315  0.69315  0.69315  0.69315
+      2   3.40120  3.40120  3.40120  0.10536      2 t - - -
+          1.38629  1.38629  1.38629  1.38629
+          0.05129  2.99573        *  0.69315  0.69315  0.00000        *
+//
+";
+
+    #[test]
+    fn test_read() {
+        let hmm = read(HMM).unwrap();
+        assert_eq!(hmm.name, "toy");
+        assert_eq!(hmm.len(), 2);
+        assert_eq!(hmm.alphabet, b"ACGT".to_vec());
+        assert_eq!(hmm.consensus, b"AT".to_vec());
+        assert!((hmm.match_emissions[1][0] - 0.9f64.ln()).abs() < 1e-4);
+        assert_eq!(hmm.transitions[2][2], f64::NEG_INFINITY);
+        assert_eq!(hmm.viterbi(b"AT").1, vec![Match, Match]);
+    }
+
+    #[test]
+    fn test_read_all() {
+        let two = [HMM, HMM].concat();
+        assert_eq!(read_all(&two[..]).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_blank_lines() {
+        let hmm = String::from_utf8(HMM.to_vec()).unwrap();
+        let blank = hmm.replace("\n      2 ", "\n\n   \n      2 ");
+        assert_eq!(read(blank.as_bytes()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_malformed() {
+        let hmm = String::from_utf8(HMM.to_vec()).unwrap();
+        let invalid =
+            |s: &str| read(s.as_bytes()).unwrap_err().kind() == io::ErrorKind::InvalidData;
+        // truncated after a blank line
+        let end = hmm.find("      2 ").unwrap();
+        assert!(invalid(&format!("{}\n\n", &hmm[..end])));
+        assert!(invalid(&hmm.replace("      2 ", "      x ")));
+        assert!(invalid(&hmm.replace("      2 ", "      3 ")));
+        assert!(invalid(
+            &hmm.replace("0.05129  2.99573        *", "0.05129")
+        ));
+        assert!(invalid(
+            &hmm.replace("3.40120  3.40120  3.40120  0.10536", "3.40120")
+        ));
+        assert!(invalid(&hmm.replace("LENG  2", "LENG  3")));
+    }
+}
@@ -0,0 +1,360 @@
+//! Position-specific scoring matrices (PSSM), also known as position weight matrices.
+//!
+//! A PSSM is built from a set of aligned, equal-length sequences (e.g. known
+//! transcription factor binding sites or a protein motif). For each position `i`
+//! and residue `a` of the alphabet, the probability is estimated with pseudocounts
+//! distributed according to the background frequencies `q`:
+//!
+//! ```text
+//! p(i, a) = (n(i, a) + β q(a)) / (N(i) + β)
+//! S(i, a) = log2(p(i, a) / q(a))
+//! ```
+//!
+//! where `n(i, a)` is the count of `a` at position `i`, `N(i)` the number of
+//! residues at that position and `β` the total pseudocount. Residues outside the
+//! alphabet (including gaps) are not counted; when scoring, they receive the
+//! lowest score of the position.
+//!
+//! The exact distribution of window scores under the background model is computed
+//! by dynamic programming over discretised scores, so that scan thresholds can be
+//! given as p-values.
+//!
+//! # Example
+//!
+//! ```
+//! use bioinformatics_algorithms::alignment::pssm::{Pssm, DNA};
+//! let sites: Vec<&[u8]> = vec![b"TATAAT", b"TATAAT", b"TACAAT", b"TATACT", b"GATAAT"];
+//! let pssm = Pssm::from_seqs(&sites, DNA, None, 1.0);
+//! let hits = pssm.scan(b"GGCTATAATGCC", 5.0);
+//! assert_eq!(hits.len(), 1);
+//! assert_eq!(hits[0].0, 3);
+//! assert!(pssm.pvalue(hits[0].1) < 0.001);
+//! ```
+//!
+//! # References
+//!
+//! - [Stormo G.D. (2000) DNA binding sites: representation and discovery. _Bioinformatics_ **16**: 16-23.](https://doi.org/10.1093/bioinformatics/16.1.16)
+//! - [Touzet H., Varré J.-S. (2007) Efficient and accurate P-value computation for Position Weight Matrices. _Algorithms Mol. Biol._ **2**: 15.](https://doi.org/10.1186/1748-7188-2-15)
+
+use crate::alignment::msa::Msa;
+use crate::utils::TextSlice;
+use bio::alphabets::dna;
+
+pub const DNA: &[u8] = b"ACGT";
+pub const PROTEIN: &[u8] = b"ACDEFGHIKLMNPQRSTVWY";
+
+/// Number of discrete steps per bit used for p-value computation
+const PVALUE_RESOLUTION: f32 = 100.0;
+
+/// Analogue of [MatchFunc](../trait.MatchFunc.html) for position-specific
+/// scoring: a profile scores residues depending on their position in the profile.
+pub trait ProfileMatchFunc {
+    /// Number of positions of the profile
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Score of residue `a` at profile position `pos`
+    fn score(&self, pos: usize, a: u8) -> f32;
+
+    /// Score of a window of exactly `self.len()` residues
+    fn score_window(&self, window: TextSlice) -> f32 {
+        window
+            .iter()
+            .enumerate()
+            .map(|(pos, &a)| self.score(pos, a))
+            .sum()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Pssm {
+    alphabet: Vec<u8>,
+    /// Index of each (uppercase or lowercase) byte in the alphabet
+    lookup: [Option<usize>; 256],
+    background: Vec<f32>,
+    /// Log-odds scores, `scores[pos][index of residue]`
+    scores: Vec<Vec<f32>>,
+}
+
+impl Pssm {
+    /// Build a PSSM from per-position residue counts.
+    ///
+    /// # Arguments
+    ///
+    /// * `counts` - `counts[pos][k]` is the (possibly weighted) count of `alphabet[k]` at `pos`
+    /// * `alphabet` - the residues, e.g. [DNA](constant.DNA.html) or [PROTEIN](constant.PROTEIN.html)
+    /// * `background` - background frequencies in alphabet order; uniform if `None`
+    /// * `pseudocount` - total pseudocount `β` added to each position
+    pub fn from_counts(
+        counts: &[Vec<f32>],
+        alphabet: &[u8],
+        background: Option<&[f32]>,
+        pseudocount: f32,
+    ) -> Self {
+        assert!(!alphabet.is_empty(), "the alphabet can't be empty");
+        assert!(pseudocount >= 0.0, "pseudocount can't be negative");
+        let background = match background {
+            Some(bg) => {
+                assert_eq!(
+                    bg.len(),
+                    alphabet.len(),
+                    "one background frequency per residue"
+                );
+                let total: f32 = bg.iter().sum();
+                bg.iter().map(|&q| q / total).collect()
+            }
+            None => vec![1.0 / alphabet.len() as f32; alphabet.len()],
+        };
+        let scores = counts
+            .iter()
+            .map(|column| {
+                assert_eq!(column.len(), alphabet.len(), "one count per residue");
+                let n: f32 = column.iter().sum();
+                column
+                    .iter()
+                    .zip(background.iter())
+                    .map(|(&c, &q)| {
+                        let p = if n + pseudocount > 0.0 {
+                            (c + pseudocount * q) / (n + pseudocount)
+                        } else {
+                            q
+                        };
+                        (p / q).log2()
+                    })
+                    .collect()
+            })
+            .collect();
+        Pssm {
+            alphabet: alphabet.to_vec(),
+            lookup: alphabet_lookup(alphabet),
+            background,
+            scores,
+        }
+    }
+
+    /// Build a PSSM from equal-length sequences, e.g. aligned binding sites.
+    /// See [from_counts](#method.from_counts) for the other arguments.
+    pub fn from_seqs<S: AsRef<[u8]>>(
+        seqs: &[S],
+        alphabet: &[u8],
+        background: Option<&[f32]>,
+        pseudocount: f32,
+    ) -> Self {
+        let len = seqs.first().map_or(0, |s| s.as_ref().len());
+        let lookup = alphabet_lookup(alphabet);
+        let mut counts = vec![vec![0f32; alphabet.len()]; len];
+        for seq in seqs {
+            let seq = seq.as_ref();
+            assert_eq!(seq.len(), len, "all sequences must have the same length");
+            for (pos, &a) in seq.iter().enumerate() {
+                if let Some(k) = lookup[a as usize] {
+                    counts[pos][k] += 1.0;
+                }
+            }
+        }
+        Self::from_counts(&counts, alphabet, background, pseudocount)
+    }
+
+    /// Build a PSSM from all columns of a multiple alignment. Gaps are not counted.
+    pub fn from_msa(
+        msa: &Msa,
+        alphabet: &[u8],
+        background: Option<&[f32]>,
+        pseudocount: f32,
+    ) -> Self {
+        Self::from_seqs(&msa.seqs, alphabet, background, pseudocount)
+    }
+
+    pub fn alphabet(&self) -> &[u8] {
+        &self.alphabet
+    }
+
+    pub fn background(&self) -> &[f32] {
+        &self.background
+    }
+
+    /// Log-odds scores, indexed by position and then by residue in alphabet order
+    pub fn scores(&self) -> &[Vec<f32>] {
+        &self.scores
+    }
+
+    /// Highest attainable window score
+    pub fn max_score(&self) -> f32 {
+        self.scores
+            .iter()
+            .map(|col| col.iter().cloned().fold(f32::MIN, f32::max))
+            .sum()
+    }
+
+    /// Lowest attainable window score
+    pub fn min_score(&self) -> f32 {
+        self.scores
+            .iter()
+            .map(|col| col.iter().cloned().fold(f32::MAX, f32::min))
+            .sum()
+    }
+
+    /// The PSSM matching the reverse complement strand, for nucleotide alphabets.
+    pub fn reverse_complement(&self) -> Self {
+        let complement_index: Vec<usize> = self
+            .alphabet
+            .iter()
+            .map(|&a| {
+                self.lookup[dna::complement(a) as usize]
+                    .expect("the alphabet must be closed under complement")
+            })
+            .collect();
+        let mut res = self.clone();
+        res.scores = self
+            .scores
+            .iter()
+            .rev()
+            .map(|col| complement_index.iter().map(|&k| col[k]).collect())
+            .collect();
+        res
+    }
+
+    /// Score every window of `seq` and report the start positions and scores of
+    /// those scoring at least `threshold`.
+    pub fn scan(&self, seq: TextSlice, threshold: f32) -> Vec<(usize, f32)> {
+        if self.is_empty() || seq.len() < self.len() {
+            return Vec::new();
+        }
+        seq.windows(self.len())
+            .enumerate()
+            .map(|(start, window)| (start, self.score_window(window)))
+            .filter(|&(_, score)| score >= threshold)
+            .collect()
+    }
+
+    /// Like [scan](#method.scan), but with the threshold given as the maximal p-value.
+    pub fn scan_pvalue(&self, seq: TextSlice, max_pvalue: f64) -> Vec<(usize, f32)> {
+        self.scan(seq, self.threshold_for_pvalue(max_pvalue))
+    }
+
+    /// Probability that a random window drawn from the background scores at least
+    /// `score`.
+    pub fn pvalue(&self, score: f32) -> f64 {
+        let (offset, dist) = self.score_distribution();
+        let t = (score * PVALUE_RESOLUTION).round() as i64 - offset;
+        dist.iter().skip(t.max(0) as usize).sum::<f64>().min(1.0)
+    }
+
+    /// Lowest score whose p-value does not exceed `pvalue`.
+    pub fn threshold_for_pvalue(&self, pvalue: f64) -> f32 {
+        let (offset, dist) = self.score_distribution();
+        let mut tail = 0.0;
+        let mut threshold = dist.len();
+        for (t, &p) in dist.iter().enumerate().rev() {
+            tail += p;
+            if tail > pvalue {
+                break;
+            }
+            threshold = t;
+        }
+        (threshold as i64 + offset) as f32 / PVALUE_RESOLUTION
+    }
+
+    /// Distribution of discretised window scores under the background model,
+    /// returned as `(offset, probabilities)` where `probabilities[t]` is the
+    /// probability of the discretised score `t + offset`. Windows containing a
+    /// residue that scores negative infinity (possible without pseudocounts)
+    /// can never pass a threshold and are left out.
+    fn score_distribution(&self) -> (i64, Vec<f64>) {
+        let discrete: Vec<Vec<Option<i64>>> = self
+            .scores
+            .iter()
+            .map(|col| {
+                col.iter()
+                    .map(|&s| {
+                        if s.is_finite() {
+                            Some((s * PVALUE_RESOLUTION).round() as i64)
+                        } else {
+                            None
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut offset = 0;
+        let mut dist = vec![1.0f64];
+        for col in discrete.iter() {
+            let min = col.iter().flatten().min().cloned().unwrap_or(0);
+            let max = col.iter().flatten().max().cloned().unwrap_or(0);
+            offset += min;
+            let mut next = vec![0.0f64; dist.len() + (max - min) as usize];
+            for (t, &p) in dist.iter().enumerate() {
+                if p == 0.0 {
+                    continue;
+                }
+                for (s, &q) in col.iter().zip(self.background.iter()) {
+                    if let Some(s) = s {
+                        next[t + (s - min) as usize] += p * q as f64;
+                    }
+                }
+            }
+            dist = next;
+        }
+        (offset, dist)
+    }
+}
+
+/// Index of each byte in `alphabet`, ignoring case
+pub(crate) fn alphabet_lookup(alphabet: &[u8]) -> [Option<usize>; 256] {
+    let mut lookup = [None; 256];
+    for (k, &a) in alphabet.iter().enumerate() {
+        lookup[a.to_ascii_uppercase() as usize] = Some(k);
+        lookup[a.to_ascii_lowercase() as usize] = Some(k);
+    }
+    lookup
+}
+
+impl ProfileMatchFunc for Pssm {
+    fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    /// Residues outside the alphabet receive the lowest score of the position.
+    fn score(&self, pos: usize, a: u8) -> f32 {
+        match self.lookup[a as usize] {
+            Some(k) => self.scores[pos][k],
+            None => self.scores[pos].iter().cloned().fold(f32::MAX, f32::min),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_odds() {
+        let sites: Vec<&[u8]> = vec![b"AC", b"AG", b"AT"];
+        let pssm = Pssm::from_seqs(&sites, DNA, None, 0.0);
+        assert!((pssm.score(0, b'A') - 2.0).abs() < 1e-6);
+        assert_eq!(pssm.score(0, b'C'), f32::NEG_INFINITY);
+        let pssm = Pssm::from_seqs(&sites, DNA, None, 1.0);
+        // (3 + 0.25) / (3 + 1) = 0.8125
+        assert!((pssm.score(0, b'a') - (0.8125f32 / 0.25).log2()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_reverse_complement() {
+        let sites: Vec<&[u8]> = vec![b"AACG", b"AACG", b"ATCG"];
+        let pssm = Pssm::from_seqs(&sites, DNA, None, 0.5);
+        let rc = pssm.reverse_complement();
+        assert!((pssm.score_window(b"AACG") - rc.score_window(b"CGTT")).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_pvalue() {
+        let sites: Vec<&[u8]> = vec![b"ACGT"];
+        let pssm = Pssm::from_seqs(&sites, DNA, None, 0.0);
+        // only ACGT scores above -inf
+        assert!((pssm.pvalue(pssm.max_score()) - 0.25f64.powi(4)).abs() < 1e-9);
+        assert_eq!(pssm.threshold_for_pvalue(0.01), pssm.max_score());
+    }
+}
@@ -1,5 +1,446 @@
-use bioinformatics_algorithms::seq_analysis::protein::ProteinSeqAnalysis;
+//! Characterise every protein of a FASTA file, which may be gzip-compressed, writing
+//! one row per protein with the properties computed by `ProteinSeqAnalysis::analyze`.
+//!
+//! ```text
+//! bioinformatics-algorithms [--format tsv|jsonl] [--ambiguous reject|ignore|expand] [FILE]
+//! ```
+//!
+//! The FASTA file is read from the standard input if `FILE` is missing or `-`. JSON
+//! Lines output has the same fields as the TSV columns; with the `serde` feature, it
+//! serializes the whole analysis of each protein and the description of its record.
+//! Records which are empty or contain invalid residues are reported on the standard
+//! error and skipped. Ambiguity codes kept with `--ambiguous ignore` or `expand`
+//! count as the mean of the residues they stand for in the masses; as they have no
+//! elemental formula, the `formula` column of such proteins is left empty.
+
+use bio::io::fasta;
+use bioinformatics_algorithms::seq_analysis::protein::alphabet::{
+    clean, expansion, AmbiguityPolicy,
+};
+use bioinformatics_algorithms::seq_analysis::protein::{AminoAcidCount, ProteinSeqAnalysis};
+use flate2::read::MultiGzDecoder;
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::process;
+
+const USAGE: &str =
+    "usage: bioinformatics-algorithms [--format tsv|jsonl] [--ambiguous reject|ignore|expand] [FILE]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Tsv,
+    JsonLines,
+}
+
+#[derive(Debug)]
+struct Options {
+    format: Format,
+    policy: AmbiguityPolicy,
+    input: Option<String>,
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut options = Options {
+        format: Format::Tsv,
+        policy: AmbiguityPolicy::default(),
+        input: None,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("missing value for {}", name));
+        match arg.as_str() {
+            "-f" | "--format" => {
+                options.format = match value(&arg)?.as_str() {
+                    "tsv" => Format::Tsv,
+                    "jsonl" => Format::JsonLines,
+                    other => return Err(format!("unknown format '{}'", other)),
+                }
+            }
+            "-a" | "--ambiguous" => {
+                options.policy = match value(&arg)?.as_str() {
+                    "reject" => AmbiguityPolicy::Reject,
+                    "ignore" => AmbiguityPolicy::Ignore,
+                    "expand" => AmbiguityPolicy::Expand,
+                    other => return Err(format!("unknown policy '{}'", other)),
+                }
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ if arg.starts_with('-') && arg != "-" => {
+                return Err(format!("unknown option '{}'", arg))
+            }
+            _ if options.input.is_none() => options.input = Some(arg),
+            _ => return Err("more than one input file".to_owned()),
+        }
+    }
+    Ok(options)
+}
+
+/// Open the input, decompressing it if it starts with the gzip magic number
+fn open(input: Option<&str>) -> io::Result<Box<dyn BufRead>> {
+    let reader: Box<dyn Read> = match input {
+        None | Some("-") => Box::new(io::stdin()),
+        Some(path) => Box::new(File::open(path)?),
+    };
+    let mut reader = BufReader::new(reader);
+    if reader.fill_buf()?.starts_with(&[0x1f, 0x8b]) {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+enum Value<'a> {
+    Text(String),
+    /// A number formatted with the `Display` of its own type
+    Number(String),
+    Composition(&'a AminoAcidCount),
+    /// A value which is not defined, empty in TSV and `null` in JSON
+    Missing,
+}
+
+fn fields<'a>(id: &str, res: &'a ProteinSeqAnalysis<'_>) -> Vec<(&'static str, Value<'a>)> {
+    use Value::*;
+    let ambiguous = res.aa_count.keys().any(|&aa| expansion(aa).is_some());
+    vec![
+        ("id", Text(id.to_owned())),
+        ("length", Number(res.n_residues().to_string())),
+        ("molecular_weight", Number(res.molecular_weight.to_string())),
+        (
+            "monoisotopic_mass",
+            Number(res.monoisotopic_mass.to_string()),
+        ),
+        (
+            "isoelectric_point",
+            Number(res.isoelectric_point.to_string()),
+        ),
+        (
+            "extinction_coefficient_reduced",
+            Number(res.molar_extinction_coefficient.0.to_string()),
+        ),
+        (
+            "extinction_coefficient_cystines",
+            Number(res.molar_extinction_coefficient.1.to_string()),
+        ),
+        (
+            "formula",
+            if ambiguous {
+                Missing
+            } else {
+                Text(res.formula.to_string())
+            },
+        ),
+        ("n_negative", Number(res.n_negative.to_string())),
+        ("n_positive", Number(res.n_positive.to_string())),
+        (
+            "half_life_mammalian",
+            Text(res.half_life.mammalian.to_string()),
+        ),
+        ("half_life_yeast", Text(res.half_life.yeast.to_string())),
+        ("half_life_e_coli", Text(res.half_life.e_coli.to_string())),
+        (
+            "instability_index",
+            Number(res.instability_index.to_string()),
+        ),
+        ("aliphatic_index", Number(res.aliphatic_index.to_string())),
+        ("gravy", Number(res.gravy.to_string())),
+        ("composition", Composition(&res.aa_count)),
+    ]
+}
+
+fn write_tsv_header<W: Write>(writer: &mut W) -> io::Result<()> {
+    let names: Vec<&str> = fields("", &ProteinSeqAnalysis::default())
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    writeln!(writer, "{}", names.join("\t"))
+}
+
+fn write_tsv<W: Write>(writer: &mut W, fields: &[(&str, Value<'_>)]) -> io::Result<()> {
+    let values: Vec<String> = fields
+        .iter()
+        .map(|(_, value)| match value {
+            Value::Text(s) => s.replace(['\t', '\n'], " "),
+            Value::Number(x) => x.clone(),
+            Value::Composition(count) => count
+                .iter()
+                .map(|(&aa, n)| format!("{}:{}", aa as char, n))
+                .collect::<Vec<_>>()
+                .join(","),
+            Value::Missing => String::new(),
+        })
+        .collect();
+    writeln!(writer, "{}", values.join("\t"))
+}
+
+#[cfg(not(feature = "serde"))]
+fn json_string(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 2);
+    res.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\t' => res.push_str("\\t"),
+            c if (c as u32) < 0x20 => res.push_str(&format!("\\u{:04x}", c as u32)),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+#[cfg(not(feature = "serde"))]
+fn write_json_line<W: Write>(writer: &mut W, fields: &[(&str, Value<'_>)]) -> io::Result<()> {
+    let members: Vec<String> = fields
+        .iter()
+        .map(|(name, value)| {
+            let value = match value {
+                Value::Text(s) => json_string(s),
+                Value::Number(x) if x.parse::<f64>().is_ok_and(f64::is_finite) => x.clone(),
+                Value::Number(_) | Value::Missing => "null".to_owned(),
+                Value::Composition(count) => format!(
+                    "{{{}}}",
+                    count
+                        .iter()
+                        .map(|(&aa, n)| format!("\"{}\":{}", aa as char, n))
+                        .collect::<Vec<_>>()
+                        .join(",")
+                ),
+            };
+            format!("{}:{}", json_string(name), value)
+        })
+        .collect();
+    writeln!(writer, "{{{}}}", members.join(","))
+}
+
+/// A protein in the JSON output: the analysis with the identifier and description of
+/// its record
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct JsonRecord<'a> {
+    id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(flatten)]
+    analysis: &'a ProteinSeqAnalysis<'a>,
+}
+
+#[cfg(feature = "serde")]
+fn write_json_record<W: Write>(
+    writer: &mut W,
+    record: &fasta::Record,
+    analysis: &ProteinSeqAnalysis<'_>,
+) -> io::Result<()> {
+    let record = JsonRecord {
+        id: record.id(),
+        description: record.desc(),
+        analysis,
+    };
+    serde_json::to_writer(&mut *writer, &record)?;
+    writeln!(writer)
+}
+
+fn run<W: Write>(options: &Options, writer: &mut W) -> io::Result<()> {
+    let reader = open(options.input.as_deref())?;
+    if options.format == Format::Tsv {
+        write_tsv_header(writer)?;
+    }
+    for record in fasta::Reader::new(reader).records() {
+        let record = record?;
+        let seq = match clean(record.seq(), options.policy) {
+            Ok(seq) if seq.is_empty() => {
+                eprintln!("{}: empty sequence, skipped", record.id());
+                continue;
+            }
+            Ok(seq) => seq,
+            Err(e) => {
+                eprintln!("{}: {}, skipped", record.id(), e);
+                continue;
+            }
+        };
+        let res = ProteinSeqAnalysis::analyze(&seq);
+        match options.format {
+            Format::Tsv => write_tsv(writer, &fields(record.id(), &res))?,
+            #[cfg(feature = "serde")]
+            Format::JsonLines => write_json_record(writer, &record, &res)?,
+            #[cfg(not(feature = "serde"))]
+            Format::JsonLines => write_json_line(writer, &fields(record.id(), &res))?,
+        }
+    }
+    writer.flush()
+}
+
 fn main() {
-    let res = ProteinSeqAnalysis::analyze(b"MAEGEITTFTALTEKFNLPPGNYKKPKLLYCSNGGHFLRILPDGTVDGTRDRSDQHIQLQLSAESVGEVYIKSTETGQYLAMDTSGLLYGSQTPSEECLFLERLEENHYNTYTSKKHAEKNWFVGLKKNGSCKRGPRTHYGQKAILFLPLPV");
-    println!("{}", res);
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    let stdout = io::stdout();
+    let mut writer = BufWriter::new(stdout.lock());
+    if let Err(e) = run(&options, &mut writer) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::path::PathBuf;
+
+    const FASTA: &[u8] = b">p1 first protein\nMKB*\n>p2\nmk\n>empty\n\n";
+
+    fn args(args: &[&str]) -> Result<Options, String> {
+        parse_args(args.iter().map(|&arg| arg.to_owned()))
+    }
+
+    /// Write `content` to a file in the temporary directory, unique to this process
+    fn temp_file(name: &str, content: &[u8]) -> PathBuf {
+        let path = env::temp_dir().join(format!("{}-{}", process::id(), name));
+        File::create(&path).unwrap().write_all(content).unwrap();
+        path
+    }
+
+    fn output(options: &Options) -> String {
+        let mut out = Vec::new();
+        run(options, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let options = args(&[]).unwrap();
+        assert_eq!(options.format, Format::Tsv);
+        assert_eq!(options.policy, AmbiguityPolicy::Expand);
+        assert_eq!(options.input, None);
+
+        let options = args(&["--format", "jsonl", "-a", "reject", "in.fa.gz"]).unwrap();
+        assert_eq!(options.format, Format::JsonLines);
+        assert_eq!(options.policy, AmbiguityPolicy::Reject);
+        assert_eq!(options.input.as_deref(), Some("in.fa.gz"));
+        assert_eq!(args(&["-"]).unwrap().input.as_deref(), Some("-"));
+
+        assert_eq!(
+            args(&["--format"]).unwrap_err(),
+            "missing value for --format"
+        );
+        assert_eq!(args(&["-f", "csv"]).unwrap_err(), "unknown format 'csv'");
+        assert_eq!(args(&["-a", "drop"]).unwrap_err(), "unknown policy 'drop'");
+        assert_eq!(
+            args(&["--verbose"]).unwrap_err(),
+            "unknown option '--verbose'"
+        );
+        assert_eq!(
+            args(&["a.fa", "b.fa"]).unwrap_err(),
+            "more than one input file"
+        );
+    }
+
+    #[test]
+    fn test_tsv_values() {
+        let count = ProteinSeqAnalysis::count_aa(b"MKK");
+        let fields = [
+            ("id", Value::Text("a\tb\nc".to_owned())),
+            ("gravy", Value::Number("-1.5".to_owned())),
+            ("formula", Value::Missing),
+            ("composition", Value::Composition(&count)),
+        ];
+        let mut out = Vec::new();
+        write_tsv(&mut out, &fields).unwrap();
+        assert_eq!(out, b"a b c\t-1.5\t\tK:2,M:1\n");
+    }
+
+    #[test]
+    fn test_tsv_rows() {
+        let path = temp_file("test_tsv_rows.fa", FASTA);
+        let options = args(&[path.to_str().unwrap()]).unwrap();
+        let out = output(&options);
+        let lines: Vec<Vec<&str>> = out.lines().map(|l| l.split('\t').collect()).collect();
+        // the empty record is skipped
+        assert_eq!(lines.len(), 3);
+        let column = |name: &str| lines[0].iter().position(|&c| c == name).unwrap();
+
+        let p1 = &lines[1];
+        assert_eq!(p1.len(), lines[0].len());
+        assert_eq!(p1[column("id")], "p1");
+        assert_eq!(p1[column("length")], "3");
+        let weight: f64 = p1[column("molecular_weight")].parse().unwrap();
+        assert!((weight - 391.98).abs() < 0.01);
+        // B has no elemental formula
+        assert_eq!(p1[column("formula")], "");
+        assert_eq!(p1[column("half_life_mammalian")], "30 hours");
+        assert_eq!(p1[column("composition")], "B:1,K:1,M:1");
+
+        let p2 = &lines[2];
+        assert_eq!(p2[column("id")], "p2");
+        assert_eq!(p2[column("formula")], "C11H23N3O3S");
+        assert_eq!(p2[column("composition")], "K:1,M:1");
+
+        let options = Options {
+            policy: AmbiguityPolicy::Reject,
+            ..options
+        };
+        let out = output(&options);
+        assert_eq!(out.lines().count(), 2);
+        assert!(out.lines().nth(1).unwrap().starts_with("p2\t"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_gzip() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(FASTA).unwrap();
+        let gzip = temp_file("test_gzip.fa.gz", &encoder.finish().unwrap());
+        let plain = temp_file("test_gzip.fa", FASTA);
+
+        let mut content = String::new();
+        open(gzip.to_str())
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content.as_bytes(), FASTA);
+        let mut content = String::new();
+        open(plain.to_str())
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content.as_bytes(), FASTA);
+        assert!(open(Some("/nonexistent/input.fa")).is_err());
+        std::fs::remove_file(gzip).unwrap();
+        std::fs::remove_file(plain).unwrap();
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let path = temp_file("test_json_lines.fa", FASTA);
+        let options = args(&["--format", "jsonl", path.to_str().unwrap()]).unwrap();
+        let out = output(&options);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with(r#"{"id":"p1","#));
+        #[cfg(not(feature = "serde"))]
+        {
+            assert!(lines[1].contains(r#""formula":"C11H23N3O3S""#));
+            assert!(lines[0].contains(r#""formula":null"#));
+            assert!(lines[0].contains(r#""composition":{"B":1,"K":1,"M":1}"#));
+        }
+        #[cfg(feature = "serde")]
+        assert!(lines[0].contains(r#""description":"first protein""#));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(not(feature = "serde"))]
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("a\"b\\c\td\u{1}"), r#""a\"b\\c\td\u0001""#);
+    }
 }
@@ -1,2 +1,3 @@
 pub mod dna;
+pub mod masking;
 pub mod protein;
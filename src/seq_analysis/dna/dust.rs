@@ -0,0 +1,171 @@
+//! DUST masking of low-complexity nucleotide regions.
+//!
+//! This is the symmetric DUST (sdust) algorithm. With `c_t` the number of
+//! occurrences of the triplet `t` among the `l` triplets of an interval, its score is
+//! `10 Σ c_t (c_t - 1) / 2 / (l - 1)` (scaled by 10 as in the reference
+//! implementations, so that the default threshold is 20). An interval is _perfect_
+//! if it scores above the threshold and at least as high as all its sub-intervals.
+//! The masked regions are the union of all perfect intervals of at most `window`
+//! bases.
+//!
+//! Triplets containing bases other than A, C, G and T (in any case) are not counted.
+//!
+//! # Example
+//!
+//! ```
+//! use bioinformatics_algorithms::seq_analysis::dna::dust::{dust, DustParams};
+//! use bioinformatics_algorithms::seq_analysis::masking::soft_mask;
+//! let mut seq = b"GATCCTGAGCTTACGGATCC".to_vec();
+//! seq.extend_from_slice(&[b'A'; 50]);
+//! seq.extend_from_slice(b"TGCCAGTTGACCTAGGCTTGA");
+//! let intervals = dust(&seq, &DustParams::default());
+//! assert_eq!(intervals.len(), 1);
+//! assert!(intervals[0].start <= 20 && intervals[0].end >= 70);
+//! let masked = soft_mask(&seq, &intervals);
+//! assert_eq!(&masked[..4], b"GATC");
+//! assert_eq!(&masked[20..24], b"aaaa");
+//! ```
+//!
+//! # References
+//!
+//! - [Morgulis A., Gertz E.M., Schäffer A.A., Agarwala R. (2006) A fast and symmetric DUST implementation to mask low-complexity DNA sequences. _J. Comput. Biol._ **13**: 1028-1040.](https://doi.org/10.1089/cmb.2006.13.1028)
+
+use crate::seq_analysis::masking::merge_intervals;
+use crate::utils::TextSlice;
+use std::cmp::Reverse;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DustParams {
+    /// Maximum length of a scored interval, in bases
+    pub window: usize,
+    /// Minimum score of a masked interval
+    pub threshold: f64,
+}
+
+impl Default for DustParams {
+    /// The defaults of sdust and BLAST: a window of 64 bases and a threshold of 20
+    fn default() -> Self {
+        DustParams {
+            window: 64,
+            threshold: 20.0,
+        }
+    }
+}
+
+fn base_index(b: u8) -> Option<usize> {
+    match b.to_ascii_uppercase() {
+        b'A' => Some(0),
+        b'C' => Some(1),
+        b'G' => Some(2),
+        b'T' => Some(3),
+        _ => None,
+    }
+}
+
+/// Index of the triplet starting at each position (`None` if it contains an
+/// ambiguous base or runs off the end of the sequence)
+fn triplets(seq: TextSlice<'_>) -> Vec<Option<usize>> {
+    (0..seq.len())
+        .map(|i| {
+            if i + 3 > seq.len() {
+                return None;
+            }
+            let mut t = 0;
+            for &b in &seq[i..i + 3] {
+                t = t * 4 + base_index(b)?;
+            }
+            Some(t)
+        })
+        .collect()
+}
+
+/// A perfect interval, with the numerator and denominator of its score
+struct Perfect {
+    start: usize,
+    r: u32,
+    l: u32,
+}
+
+/// Low-complexity intervals of `seq`, as sorted, non-overlapping, 0-based half-open
+/// ranges.
+pub fn dust(seq: TextSlice<'_>, params: &DustParams) -> Vec<Range<usize>> {
+    assert!(params.window >= 4, "window must be at least 4 bases");
+    let triplets = triplets(seq);
+    let mut masked = Vec::new();
+    // perfect intervals within the current window, by decreasing start
+    let mut perfect: Vec<Perfect> = Vec::new();
+    let mut counts = [0u32; 64];
+    for end in 3..=seq.len() {
+        let min_start = end.saturating_sub(params.window);
+        perfect.retain(|p| p.start >= min_start);
+        counts.iter_mut().for_each(|c| *c = 0);
+        let (mut r, mut n) = (0u32, 0u32);
+        // best score among the perfect intervals contained in [start, end)
+        let (mut max_r, mut max_l) = (0u32, 1u32);
+        let mut j = 0;
+        let mut new_perfect = Vec::new();
+        for start in (min_start..=end - 3).rev() {
+            let t = match triplets[start] {
+                Some(t) => t,
+                None => continue,
+            };
+            r += counts[t];
+            counts[t] += 1;
+            n += 1;
+            if n < 2 {
+                continue;
+            }
+            let l = n - 1;
+            while j < perfect.len() && perfect[j].start >= start {
+                if perfect[j].r * max_l > max_r * perfect[j].l {
+                    max_r = perfect[j].r;
+                    max_l = perfect[j].l;
+                }
+                j += 1;
+            }
+            if r as f64 * 10.0 > params.threshold * l as f64 && r * max_l >= max_r * l {
+                max_r = r;
+                max_l = l;
+                masked.push(start..end);
+                new_perfect.push(Perfect { start, r, l });
+            }
+        }
+        perfect.extend(new_perfect);
+        perfect.sort_by_key(|p| Reverse(p.start));
+    }
+    merge_intervals(masked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dust() {
+        let params = DustParams::default();
+        // a random-looking sequence has no low-complexity region
+        let seq = b"GATCCTGAGCTTACGGATCACGTGACTGCAGTCAAGTCCATGGACTTGACAGCTATGCGATCG";
+        assert!(dust(seq, &params).is_empty());
+        // a dinucleotide repeat embedded in it is masked
+        let mut repeat = seq[..20].to_vec();
+        repeat.extend_from_slice(&b"CA".repeat(30));
+        repeat.extend_from_slice(&seq[20..]);
+        // (the flanking "CA" before and "C" after extend it)
+        assert_eq!(dust(&repeat, &params), vec![18..81]);
+        // it scores about 10 * l / 4 < 200
+        let params = DustParams {
+            window: 64,
+            threshold: 200.0,
+        };
+        assert!(dust(&repeat, &params).is_empty());
+    }
+
+    #[test]
+    fn test_ambiguous_bases() {
+        assert!(dust(&[b'N'; 100], &DustParams::default()).is_empty());
+        let mut seq = b"acgt".to_vec();
+        seq.extend_from_slice(&[b'a'; 50]);
+        assert_eq!(dust(&seq, &DustParams::default()), vec![4..54]);
+    }
+}
@@ -0,0 +1,62 @@
+//! Masking of sequence intervals, e.g. low-complexity regions found by
+//! [SEG](../protein/seg/index.html) or [DUST](../dna/dust/index.html).
+//!
+//! Soft-masked copies have the masked residues in lower case, as expected by most
+//! search tools; hard-masked copies have them replaced by a mask character, usually
+//! `X` for proteins and `N` for nucleotides.
+
+use crate::utils::TextSlice;
+use std::ops::Range;
+
+/// Copy of `seq` with the residues in `intervals` in lower case
+pub fn soft_mask(seq: TextSlice<'_>, intervals: &[Range<usize>]) -> Vec<u8> {
+    let mut res = seq.to_vec();
+    for interval in intervals {
+        res[interval.clone()].make_ascii_lowercase();
+    }
+    res
+}
+
+/// Copy of `seq` with the residues in `intervals` replaced by `mask`
+pub fn hard_mask(seq: TextSlice<'_>, intervals: &[Range<usize>], mask: u8) -> Vec<u8> {
+    let mut res = seq.to_vec();
+    for interval in intervals {
+        for c in &mut res[interval.clone()] {
+            *c = mask;
+        }
+    }
+    res
+}
+
+/// Merge overlapping or adjacent intervals, sorting them by start.
+pub fn merge_intervals(mut intervals: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    intervals.sort_by_key(|r| r.start);
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(intervals.len());
+    for r in intervals {
+        match merged.last_mut() {
+            Some(last) if last.end >= r.start => last.end = last.end.max(r.end),
+            _ => merged.push(r),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask() {
+        let intervals = [1..3, 5..6];
+        assert_eq!(soft_mask(b"ACGTACG", &intervals), b"AcgTAcG".to_vec());
+        assert_eq!(hard_mask(b"ACGTACG", &intervals, b'N'), b"ANNTANG".to_vec());
+    }
+
+    #[test]
+    fn test_merge_intervals() {
+        assert_eq!(
+            merge_intervals(vec![5..8, 0..2, 1..4, 8..9, 12..13]),
+            vec![0..4, 5..9, 12..13]
+        );
+    }
+}
@@ -5,64 +5,87 @@
 //!   Protein Identification and Analysis Tools on the ExPASy Server, in _The Proteomics Protocols
 //!   Handbook_, Humana Press (2005). pp. 571-607
 
+pub mod aaindex;
+pub mod alphabet;
+pub mod cyclopeptide;
+pub mod digestion;
+pub mod disorder;
+pub mod fragmentation;
+pub mod hydrophobic_moment;
+pub mod isotopes;
+pub mod mass;
+pub mod mass_fingerprint;
+pub mod modification;
+pub mod params;
+pub mod profile;
+pub mod secondary_structure;
+pub mod seg;
+pub mod titration;
+pub mod transmembrane;
+
 use crate::utils::TextSlice;
+use digestion::{DigestParams, Peptide, Protease};
+use disorder::{DisorderParams, DisorderPrediction};
+use mass::{ElementalFormula, RESIDUE_FORMULAS};
+pub use params::half_life::HalfLife;
+pub use params::isoelectric_point::{Charge, PkaScale};
+use profile::{Profile, WindowParams};
 use std::collections::BTreeMap;
 use std::fmt;
+use titration::TitrationCurve;
 
 pub type AminoAcidCount = BTreeMap<u8, u32>;
 pub type AminoAcidPercentage = BTreeMap<u8, f32>;
 
-use lazy_static::lazy_static;
-
-// -------------- used for calculating isoelectric point -------------------
-const N_TERM_PKA_DEFAULT: f32 = 7.5;
-const C_TERM_PKA_DEFAULT: f32 = 3.55;
-
-pub enum Charge {
-    Positive,
-    Negative,
-}
-
-lazy_static! {
-    pub static ref pKa_table: BTreeMap<u8, (f32, Charge)> = {
-        let mut m = BTreeMap::new();
-        m.insert(b'K', (10.0, Charge::Positive));
-        m.insert(b'R', (12.0, Charge::Positive));
-        m.insert(b'H', (5.98, Charge::Positive));
-        m.insert(b'D', (4.05, Charge::Negative));
-        m.insert(b'E', (4.45, Charge::Negative));
-        m.insert(b'C', (9.00, Charge::Negative));
-        m.insert(b'Y', (10.0, Charge::Negative));
-        m
-    };
-    pub static ref n_terminal_pKa_table: BTreeMap<u8, f32> = {
-        let mut m = BTreeMap::new();
-        m.insert(b'A', 7.59);
-        m.insert(b'M', 7.00);
-        m.insert(b'S', 6.93);
-        m.insert(b'P', 8.36);
-        m.insert(b'T', 6.82);
-        m.insert(b'V', 7.44);
-        m.insert(b'E', 7.70);
-        m
-    };
-    pub static ref c_terminal_pKa_table: BTreeMap<u8, f32> = {
-        let mut m = BTreeMap::new();
-        m.insert(b'D', 4.55);
-        m.insert(b'E', 4.75);
-        m
-    };
+/// Estimated half-life of a protein, given its N-terminal residue
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EstimatedHalfLife {
+    /// Mammalian reticulocytes, in vitro
+    pub mammalian: HalfLife,
+    /// Yeast, in vivo
+    pub yeast: HalfLife,
+    /// _Escherichia coli_, in vivo
+    pub e_coli: HalfLife,
 }
 
-// --------------------------------
-
+/// Properties of a protein sequence. With the `serde` feature, the analysis can be
+/// serialized, the sequence and the residues being written as strings; see
+/// [`OwnedProteinSeqAnalysis`](struct.OwnedProteinSeqAnalysis.html) to deserialize it.
 #[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ProteinSeqAnalysis<'a> {
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::utils::serde_text::serialize")
+    )]
     pub seq: TextSlice<'a>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::utils::serde_residue_map::serialize")
+    )]
     pub aa_count: AminoAcidCount,
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::utils::serde_residue_map::serialize")
+    )]
     pub aa_percentages: AminoAcidPercentage,
     pub isoelectric_point: f32,
     pub molar_extinction_coefficient: (u32, u32),
+    pub formula: ElementalFormula,
+    /// Average molecular weight, in Da
+    pub molecular_weight: f64,
+    /// Monoisotopic mass, in Da
+    pub monoisotopic_mass: f64,
+    /// Number of negatively charged residues (Asp + Glu)
+    pub n_negative: u32,
+    /// Number of positively charged residues (Arg + Lys)
+    pub n_positive: u32,
+    pub half_life: EstimatedHalfLife,
+    pub instability_index: f32,
+    pub aliphatic_index: f32,
+    /// Grand average of hydropathicity
+    pub gravy: f32,
 }
 
 impl<'a> ProteinSeqAnalysis<'a> {
@@ -75,30 +98,211 @@ impl<'a> ProteinSeqAnalysis<'a> {
 
     pub fn analyze(seq: TextSlice<'a>) -> Self {
         let mut res = Self::new(seq);
-        res.isoelectric_point = res.calc_isoelectric_point();
+        res.isoelectric_point = res.calc_isoelectric_point(&PkaScale::default());
         res.molar_extinction_coefficient = res.calc_molar_extinction_coefficient();
         res.aa_percentages = res.calc_aa_percentages();
+        res.formula = res.calc_formula();
+        res.molecular_weight = res.calc_molecular_weight();
+        res.monoisotopic_mass = res.calc_monoisotopic_mass();
+        let (n_negative, n_positive) = res.count_charged_residues();
+        res.n_negative = n_negative;
+        res.n_positive = n_positive;
+        res.half_life = res.calc_half_life();
+        res.instability_index = res.calc_instability_index();
+        res.aliphatic_index = res.calc_aliphatic_index();
+        res.gravy = res.calc_gravy();
         res
     }
 
+    /// Copy the sequence, so that the analysis no longer borrows it
+    pub fn into_owned(self) -> OwnedProteinSeqAnalysis {
+        OwnedProteinSeqAnalysis {
+            seq: self.seq.to_vec(),
+            aa_count: self.aa_count,
+            aa_percentages: self.aa_percentages,
+            isoelectric_point: self.isoelectric_point,
+            molar_extinction_coefficient: self.molar_extinction_coefficient,
+            formula: self.formula,
+            molecular_weight: self.molecular_weight,
+            monoisotopic_mass: self.monoisotopic_mass,
+            n_negative: self.n_negative,
+            n_positive: self.n_positive,
+            half_life: self.half_life,
+            instability_index: self.instability_index,
+            aliphatic_index: self.aliphatic_index,
+            gravy: self.gravy,
+        }
+    }
+
+    /// Count the residues of `seq`, in upper case. Characters other than letters are
+    /// skipped; use [`alphabet::clean`](alphabet/fn.clean.html) to validate sequences.
     pub fn count_aa(seq: TextSlice) -> AminoAcidCount {
         let mut res: BTreeMap<u8, u32> = BTreeMap::new();
-        for &aa in seq {
-            let count = res.entry(aa).or_insert(0);
+        for &aa in seq.iter().filter(|aa| aa.is_ascii_alphabetic()) {
+            let count = res.entry(aa.to_ascii_uppercase()).or_insert(0);
             *count += 1;
         }
         res
     }
 
+    /// Residues of the sequence, in upper case, skipping characters other than letters
+    /// as [`count_aa`](#method.count_aa) does
+    pub fn residues(&self) -> Vec<u8> {
+        self.seq
+            .iter()
+            .filter(|aa| aa.is_ascii_alphabetic())
+            .map(|aa| aa.to_ascii_uppercase())
+            .collect()
+    }
+
+    /// Number of residues, which differs from the length of the sequence if it
+    /// contains whitespace, a stop codon or other characters than letters
+    pub fn n_residues(&self) -> usize {
+        self.aa_count.values().sum::<u32>() as usize
+    }
+
+    /// First and last residues, in upper case
+    fn termini(&self) -> Option<(u8, u8)> {
+        let mut residues = self.seq.iter().filter(|aa| aa.is_ascii_alphabetic());
+        let first = residues.next()?.to_ascii_uppercase();
+        let last = residues
+            .next_back()
+            .map_or(first, |aa| aa.to_ascii_uppercase());
+        Some((first, last))
+    }
+
     pub fn calc_aa_percentages(&self) -> AminoAcidPercentage {
         let mut percentages: AminoAcidPercentage = BTreeMap::new();
-        let len = self.seq.len();
+        let len = self.n_residues();
         for (&aa, &count) in self.aa_count.iter() {
             percentages.insert(aa, count as f32 / len as f32);
         }
         percentages
     }
 
+    /// Elemental formula of the (unmodified) polypeptide chain, including the water of
+    /// the free N- and C-termini. Ambiguous residues are left out.
+    pub fn calc_formula(&self) -> ElementalFormula {
+        mass::formula_from_counts(self.aa_count.iter())
+    }
+
+    /// Mass of the ambiguous residues, as the weighted mean of their expansions
+    fn ambiguous_mass(&self, mass: fn(&ElementalFormula) -> f64) -> f64 {
+        self.aa_count
+            .iter()
+            .filter_map(|(&aa, &n)| {
+                alphabet::expansion(aa).map(|residues| {
+                    n as f64
+                        * residues
+                            .iter()
+                            .map(|(residue, weight)| weight * mass(&RESIDUE_FORMULAS[residue]))
+                            .sum::<f64>()
+                })
+            })
+            .sum()
+    }
+
+    /// Average molecular weight, in Da, counting ambiguous residues as the mean of the
+    /// residues they stand for
+    pub fn calc_molecular_weight(&self) -> f64 {
+        self.calc_formula().average_mass() + self.ambiguous_mass(ElementalFormula::average_mass)
+    }
+
+    /// Monoisotopic mass, in Da, counting ambiguous residues as the mean of the
+    /// residues they stand for
+    pub fn calc_monoisotopic_mass(&self) -> f64 {
+        self.calc_formula().monoisotopic_mass()
+            + self.ambiguous_mass(ElementalFormula::monoisotopic_mass)
+    }
+
+    fn count(&self, aa: u8) -> u32 {
+        self.aa_count.get(&aa).cloned().unwrap_or(0)
+    }
+
+    /// Number of negatively (Asp + Glu) and positively (Arg + Lys) charged residues
+    pub fn count_charged_residues(&self) -> (u32, u32) {
+        (
+            self.count(b'D') + self.count(b'E'),
+            self.count(b'R') + self.count(b'K'),
+        )
+    }
+
+    /// Estimate the half-life from the N-terminal residue, according to the N-end rule
+    pub fn calc_half_life(&self) -> EstimatedHalfLife {
+        match self
+            .termini()
+            .and_then(|(n_term, _)| params::half_life::N_END_RULE.get(&n_term))
+        {
+            Some(&[mammalian, yeast, e_coli]) => EstimatedHalfLife {
+                mammalian,
+                yeast,
+                e_coli,
+            },
+            None => EstimatedHalfLife::default(),
+        }
+    }
+
+    /// Instability index of Guruprasad _et al_ (1990), computed from the weights of all
+    /// dipeptides in the sequence. Dipeptides containing non-standard residues are
+    /// skipped.
+    pub fn calc_instability_index(&self) -> f32 {
+        use params::{amino_acid_index, instability_index::DIWV};
+        let residues = self.residues();
+        let sum: f32 = residues
+            .windows(2)
+            .filter_map(|w| Some(DIWV[amino_acid_index(w[0])?][amino_acid_index(w[1])?]))
+            .sum();
+        10.0 / residues.len() as f32 * sum
+    }
+
+    /// Whether the protein is predicted to be stable, i.e. its instability index is
+    /// below 40
+    pub fn is_stable(&self) -> bool {
+        self.instability_index < params::instability_index::STABILITY_THRESHOLD
+    }
+
+    /// Aliphatic index of Ikai (1980), the relative volume occupied by the aliphatic
+    /// side chains of Ala, Val, Ile and Leu
+    pub fn calc_aliphatic_index(&self) -> f32 {
+        let len = self.n_residues() as f32;
+        let mole_percent = |aa| self.count(aa) as f32 / len * 100.0;
+        mole_percent(b'A')
+            + 2.9 * mole_percent(b'V')
+            + 3.9 * (mole_percent(b'I') + mole_percent(b'L'))
+    }
+
+    /// Grand average of hydropathicity: the sum of the Kyte & Doolittle hydropathy
+    /// values of all residues, divided by the sequence length
+    pub fn calc_gravy(&self) -> f32 {
+        let sum: f32 = self
+            .aa_count
+            .iter()
+            .filter_map(|(aa, &n)| Some(params::hydropathy::KYTE_DOOLITTLE.get(aa)? * n as f32))
+            .sum();
+        sum / self.n_residues() as f32
+    }
+
+    /// Sliding-window profile of the [`residues`](#method.residues) on a residue scale,
+    /// such as `params::hydropathy::KYTE_DOOLITTLE`
+    pub fn profile(&self, scale: &BTreeMap<u8, f32>, params: &WindowParams) -> Profile {
+        profile::profile(&self.residues(), scale, params)
+    }
+
+    /// Per-residue FoldIndex disorder scores and disordered segments of the
+    /// [`residues`](#method.residues)
+    pub fn predict_disorder(&self, params: &DisorderParams) -> DisorderPrediction {
+        disorder::predict(&self.residues(), params)
+    }
+
+    /// Peptides from an in-silico digestion of the [`residues`](#method.residues) by
+    /// `protease`. Their positions are those in the residues.
+    pub fn digest(&self, protease: &Protease, params: &DigestParams) -> Vec<Peptide<'static>> {
+        digestion::digest(&self.residues(), protease, params)
+            .into_iter()
+            .map(Peptide::into_owned)
+            .collect()
+    }
+
     /// Calculate the molar extinction coefficient (at 280 nm)
     ///
     /// Calculates the molar extinction coefficient assuming cysteines (reduced) and cystines residues (oxidised)
@@ -117,60 +321,88 @@ impl<'a> ProteinSeqAnalysis<'a> {
         (mec_reduced, mec_oxidised)
     }
 
-    /// Estimate the isoelectric point of a polypeptide chain based on its primary structure.
-    pub fn calc_isoelectric_point(&self) -> f32 {
-        self.pi_recursive(4.05, 12.0, 7.775)
+    /// Estimate the isoelectric point of a polypeptide chain based on its primary structure,
+    /// using the given pKa scale. [ProtParam](https://web.expasy.org/protparam/) uses
+    /// `PkaScale::bjellqvist()`, the default.
+    pub fn calc_isoelectric_point(&self, scale: &PkaScale) -> f32 {
+        self.ph_recursive(scale, 0.0, 0.0, 14.0, 7.0)
     }
 
-    fn pi_recursive(&self, mut x1: f32, mut x2: f32, xmid: f32) -> f32 {
+    /// pH (between 0 and 14) at which the polypeptide chain has the given net charge,
+    /// or `None` if that charge is not reached within this range
+    pub fn ph_at_charge(&self, charge: f32, scale: &PkaScale) -> Option<f32> {
+        // the net charge decreases monotonically with pH
+        if charge > self.charge_at_pH(0.0, scale) || charge < self.charge_at_pH(14.0, scale) {
+            return None;
+        }
+        Some(self.ph_recursive(scale, charge, 0.0, 14.0, 7.0))
+    }
+
+    fn ph_recursive(
+        &self,
+        scale: &PkaScale,
+        target: f32,
+        mut x1: f32,
+        mut x2: f32,
+        xmid: f32,
+    ) -> f32 {
         if x2 - x1 < 0.0001 {
             return xmid;
         }
-        let charge = self.charge_at_pH(xmid);
-        if charge > 0.0 {
+        let charge = self.charge_at_pH(xmid, scale);
+        if charge > target {
             x1 = xmid;
         } else {
             x2 = xmid;
         }
-        self.pi_recursive(x1, x2, (x1 + x2) / 2.0)
+        self.ph_recursive(scale, target, x1, x2, (x1 + x2) / 2.0)
     }
-    pub fn charge_at_pH(&self, pH: f32) -> f32 {
+
+    /// Net charge sampled from `ph_min` to `ph_max` every `step` pH units
+    pub fn titration_curve(
+        &self,
+        scale: &PkaScale,
+        ph_min: f32,
+        ph_max: f32,
+        step: f32,
+    ) -> TitrationCurve {
+        TitrationCurve::sample(|ph| self.charge_at_pH(ph, scale), ph_min, ph_max, step)
+    }
+
+    /// Net charge of the polypeptide chain at the given pH, using the given pKa scale.
+    /// Ambiguous residues contribute the mean charge of the residues they stand for.
+    pub fn charge_at_pH(&self, pH: f32, scale: &PkaScale) -> f32 {
         let mut charge = 0f32;
-        for (aa, &count) in self.aa_count.iter() {
-            if let Some((pKa, positivity)) = pKa_table.get(aa) {
+        for (aa, count) in alphabet::expand_counts(&self.aa_count) {
+            let count = count as f32;
+            if let Some((pKa, positivity)) = scale.side_chains.get(&aa) {
                 match positivity {
                     Charge::Negative => {
                         let partial_charge = 1.0 / (10f32.powf(pKa - pH) + 1.0);
-                        charge -= partial_charge * count as f32;
+                        charge -= partial_charge * count;
                     }
                     Charge::Positive => {
                         let partial_charge = 1.0 / (10f32.powf(pH - pKa) + 1.0);
-                        charge += partial_charge * count as f32;
+                        charge += partial_charge * count;
                     }
                 }
             }
         }
-        let n_term_pKa = if let Some(&pKa) = n_terminal_pKa_table.get(&self.seq[0]) {
-            pKa
-        } else {
-            N_TERM_PKA_DEFAULT
-        };
-        charge += 1.0 / (10f32.powf(pH - n_term_pKa) + 1.0);
-        let c_term_pKa = if let Some(&pKa) = c_terminal_pKa_table.get(&self.seq[self.seq.len() - 1])
-        {
-            pKa
-        } else {
-            C_TERM_PKA_DEFAULT
-        };
-        charge -= 1.0 / (10f32.powf(c_term_pKa - pH) + 1.0);
+        if let Some((n_term, c_term)) = self.termini() {
+            let n_term_pKa = scale.n_term_pka(n_term);
+            charge += 1.0 / (10f32.powf(pH - n_term_pKa) + 1.0);
+            let c_term_pKa = scale.c_term_pka(c_term);
+            charge -= 1.0 / (10f32.powf(c_term_pKa - pH) + 1.0);
+        }
         charge
     }
 }
 
 impl<'a> fmt::Display for ProteinSeqAnalysis<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "Number of amino acids: {}", self.seq.len())?;
-        writeln!(f, "Molecular weight: {}", "<not implemented>")?;
+        writeln!(f, "Number of amino acids: {}", self.n_residues())?;
+        writeln!(f, "Molecular weight: {:.2}", self.molecular_weight)?;
+        writeln!(f, "Monoisotopic mass: {:.4}", self.monoisotopic_mass)?;
         writeln!(f, "Theoretical pI: {}", self.isoelectric_point)?;
         writeln!(
             f,
@@ -186,6 +418,46 @@ impl<'a> fmt::Display for ProteinSeqAnalysis<'a> {
             "     {}, assuming all pairs of Cys residues form cystines",
             self.molar_extinction_coefficient.1
         )?;
+        writeln!(f, "Formula: {}", self.formula)?;
+        writeln!(f, "Total number of atoms: {}", self.formula.total_atoms())?;
+        writeln!(
+            f,
+            "Total number of negatively charged residues (Asp + Glu): {}",
+            self.n_negative
+        )?;
+        writeln!(
+            f,
+            "Total number of positively charged residues (Arg + Lys): {}",
+            self.n_positive
+        )?;
+        writeln!(f, "Estimated half-life:")?;
+        writeln!(
+            f,
+            "     {} (mammalian reticulocytes, in vitro)",
+            self.half_life.mammalian
+        )?;
+        writeln!(f, "     {} (yeast, in vivo)", self.half_life.yeast)?;
+        writeln!(
+            f,
+            "     {} (Escherichia coli, in vivo)",
+            self.half_life.e_coli
+        )?;
+        writeln!(f, "Instability index: {:.2}", self.instability_index)?;
+        writeln!(
+            f,
+            "This classifies the protein as {}.",
+            if self.is_stable() {
+                "stable"
+            } else {
+                "unstable"
+            }
+        )?;
+        writeln!(f, "Aliphatic index: {:.2}", self.aliphatic_index)?;
+        writeln!(
+            f,
+            "Grand average of hydropathicity (GRAVY): {:.3}",
+            self.gravy
+        )?;
         writeln!(f, "┏━━━━━━━━━━━━━━━━━━━━━━━━┓")?;
         writeln!(f, "┃{}┃", "Residue│count│percentage")?;
         writeln!(f, "┠———————┼—————┼——————————┨")?;
@@ -203,9 +475,67 @@ impl<'a> fmt::Display for ProteinSeqAnalysis<'a> {
     }
 }
 
+/// A [`ProteinSeqAnalysis`](struct.ProteinSeqAnalysis.html) owning its sequence, which
+/// can be stored, or deserialized with the `serde` feature
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedProteinSeqAnalysis {
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_text"))]
+    pub seq: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_residue_map"))]
+    pub aa_count: AminoAcidCount,
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_residue_map"))]
+    pub aa_percentages: AminoAcidPercentage,
+    pub isoelectric_point: f32,
+    pub molar_extinction_coefficient: (u32, u32),
+    pub formula: ElementalFormula,
+    /// Average molecular weight, in Da
+    pub molecular_weight: f64,
+    /// Monoisotopic mass, in Da
+    pub monoisotopic_mass: f64,
+    /// Number of negatively charged residues (Asp + Glu)
+    pub n_negative: u32,
+    /// Number of positively charged residues (Arg + Lys)
+    pub n_positive: u32,
+    pub half_life: EstimatedHalfLife,
+    pub instability_index: f32,
+    pub aliphatic_index: f32,
+    /// Grand average of hydropathicity
+    pub gravy: f32,
+}
+
+impl OwnedProteinSeqAnalysis {
+    /// Borrow the analysis, e.g. to display it or to compute other properties
+    pub fn as_analysis(&self) -> ProteinSeqAnalysis<'_> {
+        ProteinSeqAnalysis {
+            seq: &self.seq,
+            aa_count: self.aa_count.clone(),
+            aa_percentages: self.aa_percentages.clone(),
+            isoelectric_point: self.isoelectric_point,
+            molar_extinction_coefficient: self.molar_extinction_coefficient,
+            formula: self.formula.clone(),
+            molecular_weight: self.molecular_weight,
+            monoisotopic_mass: self.monoisotopic_mass,
+            n_negative: self.n_negative,
+            n_positive: self.n_positive,
+            half_life: self.half_life,
+            instability_index: self.instability_index,
+            aliphatic_index: self.aliphatic_index,
+            gravy: self.gravy,
+        }
+    }
+}
+
+impl fmt::Display for OwnedProteinSeqAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_analysis().fmt(f)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use lazy_static::lazy_static;
 
     static S1: &[u8;152] = &b"MAEGEITTFTALTEKFNLPPGNYKKPKLLYCSNGGHFLRILPDGTVDGTRDRSDQHIQLQLSAESVGEVYIKSTETGQYLAMDTSGLLYGSQTPSEECLFLERLEENHYNTYTSKKHAEKNWFVGLKKNGSCKRGPRTHYGQKAILFLPLPV";
 
@@ -228,8 +558,210 @@ mod tests {
         assert!((RES1.isoelectric_point - 7.72).abs() < 0.01)
     }
 
+    #[test]
+    fn test_pka_scales() {
+        // pI and net charges at pH 4 and 11 of S1, computed independently with the
+        // model of IPC: the Henderson-Hasselbalch equation and the pKa values
+        // tabulated by Kozlowski (2016). ExPASy's Compute pI/Mw gives 7.72. The pI
+        // depends mostly on the pKa of the N-terminus, Cys, His and Tyr, the charges
+        // on those of the C-terminus, Asp and Glu (pH 4) or Lys and Arg (pH 11).
+        for &(name, pi, acidic, basic) in &[
+            ("emboss", 8.07, 15.17, -17.63),
+            ("lehninger", 7.98, 15.75, -19.57),
+            ("expasy", 7.72, 17.71, -21.70),
+            ("sillero", 8.18, 17.73, -20.38),
+            ("solomon", 7.96, 16.18, -19.36),
+            ("rodwell", 7.78, 15.36, -14.48),
+            ("ipc_protein", 7.22, 16.74, -20.29),
+        ] {
+            let scale = PkaScale::from_name(name).unwrap();
+            assert!(
+                (RES1.calc_isoelectric_point(&scale) - pi).abs() < 0.01,
+                "{}",
+                name
+            );
+            assert!(
+                (RES1.charge_at_pH(4.0, &scale) - acidic).abs() < 0.01,
+                "{}",
+                name
+            );
+            assert!(
+                (RES1.charge_at_pH(11.0, &scale) - basic).abs() < 0.01,
+                "{}",
+                name
+            );
+        }
+        assert!(PkaScale::from_name("unknown").is_none());
+    }
+
+    #[test]
+    fn test_user_supplied_pka_scale() {
+        // a single lysine and free termini: pI halfway between the amino pKas
+        let scale = PkaScale::new(&[(b'K', 10.0, Charge::Positive)], 8.0, 3.0);
+        let res = ProteinSeqAnalysis::new(b"GKG");
+        assert!((res.calc_isoelectric_point(&scale) - 9.0).abs() < 0.01);
+        assert!((res.charge_at_pH(7.0, &scale) - 0.9).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_ph_at_charge() {
+        let scale = PkaScale::default();
+        assert_eq!(
+            RES1.ph_at_charge(0.0, &scale),
+            Some(RES1.calc_isoelectric_point(&scale))
+        );
+        let ph = RES1.ph_at_charge(5.0, &scale).unwrap();
+        assert!(ph < RES1.isoelectric_point);
+        assert!((RES1.charge_at_pH(ph, &scale) - 5.0).abs() < 0.01);
+        assert_eq!(RES1.ph_at_charge(100.0, &scale), None);
+    }
+
     #[test]
     fn test_molar_extinction_coefficient() {
         assert_eq!(RES1.molar_extinction_coefficient, (17420, 17545))
     }
+
+    #[test]
+    fn test_molecular_weight() {
+        assert_eq!(RES1.formula.to_string(), "C763H1188N206O231S5");
+        assert!((RES1.molecular_weight - 17103.37).abs() < 0.05);
+        assert!((RES1.monoisotopic_mass - 17092.61).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_charged_residues() {
+        let res = ProteinSeqAnalysis::analyze(b"MDEKRRA");
+        assert_eq!((res.n_negative, res.n_positive), (2, 3));
+    }
+
+    #[test]
+    fn test_half_life() {
+        assert_eq!(RES1.half_life.mammalian, HalfLife::Hours(30.0));
+        assert_eq!(RES1.half_life.yeast, HalfLife::MoreThanHours(20.0));
+        assert_eq!(RES1.half_life.e_coli, HalfLife::MoreThanHours(10.0));
+        let res = ProteinSeqAnalysis::analyze(b"PAG");
+        assert_eq!(res.half_life.e_coli, HalfLife::Unknown);
+    }
+
+    #[test]
+    fn test_instability_index() {
+        // AA: 1.0, AC: 44.94, CA: 1.0
+        let res = ProteinSeqAnalysis::analyze(b"AACA");
+        assert!((res.instability_index - 10.0 / 4.0 * 46.94).abs() < 1e-4);
+        assert!(!res.is_stable());
+        assert!(ProteinSeqAnalysis::analyze(b"AAAA").is_stable());
+    }
+
+    #[test]
+    fn test_aliphatic_index() {
+        let res = ProteinSeqAnalysis::analyze(b"AVIL");
+        assert!((res.aliphatic_index - 25.0 * (1.0 + 2.9 + 3.9 * 2.0)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_gravy() {
+        let res = ProteinSeqAnalysis::analyze(b"IR");
+        assert!(res.gravy.abs() < 1e-6);
+        assert!((RES1.gravy - -0.597).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_non_standard_residues() {
+        let count = ProteinSeqAnalysis::count_aa(b"ac c*\n");
+        assert_eq!(count.get(&b'C'), Some(&2));
+        assert_eq!(count.values().sum::<u32>(), 3);
+
+        let sec = ProteinSeqAnalysis::new(b"GUG");
+        assert!((sec.calc_monoisotopic_mass() - 283.00713).abs() < 1e-4);
+        assert_eq!(sec.calc_formula().to_string(), "C7H13N3O4Se");
+        let pyl = ProteinSeqAnalysis::new(b"O");
+        assert!((pyl.calc_monoisotopic_mass() - 255.15829).abs() < 1e-4);
+        // selenocysteine is acidic
+        let scale = PkaScale::default();
+        assert!(
+            sec.charge_at_pH(7.0, &scale)
+                < ProteinSeqAnalysis::new(b"GGG").charge_at_pH(7.0, &scale)
+        );
+
+        // ambiguous residues count as the mean of their expansions
+        let b = ProteinSeqAnalysis::new(b"GBG");
+        let d = ProteinSeqAnalysis::new(b"GDG");
+        let n = ProteinSeqAnalysis::new(b"GNG");
+        let mean = |f: &dyn Fn(&ProteinSeqAnalysis) -> f64| (f(&d) + f(&n)) / 2.0;
+        assert!((b.calc_molecular_weight() - mean(&|p| p.calc_molecular_weight())).abs() < 1e-9);
+        assert!((b.calc_monoisotopic_mass() - mean(&|p| p.calc_monoisotopic_mass())).abs() < 1e-9);
+        let charge = b.charge_at_pH(7.0, &scale) as f64;
+        assert!((charge - mean(&|p| p.charge_at_pH(7.0, &scale) as f64)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_raw_input() {
+        let raw = ProteinSeqAnalysis::analyze(b"mkv*\n").into_owned();
+        let clean = ProteinSeqAnalysis::analyze(b"MKV").into_owned();
+        assert_eq!(raw.seq, b"mkv*\n");
+        assert_eq!(
+            OwnedProteinSeqAnalysis {
+                seq: clean.seq.clone(),
+                ..raw.clone()
+            },
+            clean
+        );
+        assert_eq!(raw.as_analysis().n_residues(), 3);
+        assert_eq!(raw.as_analysis().residues(), b"MKV");
+        assert_eq!(raw.half_life.mammalian, HalfLife::Hours(30.0));
+    }
+
+    #[test]
+    fn test_raw_input_methods() {
+        let raw = ProteinSeqAnalysis::new(b"mak trg\nsk*");
+        let clean = ProteinSeqAnalysis::new(b"MAKTRGSK");
+
+        let trypsin = Protease::trypsin();
+        let peptides = raw.digest(&trypsin, &DigestParams::default());
+        assert_eq!(peptides, clean.digest(&trypsin, &DigestParams::default()));
+        let seqs: Vec<&[u8]> = peptides.iter().map(|p| &*p.seq).collect();
+        assert_eq!(seqs, vec![&b"MAK"[..], b"TR", b"GSK"]);
+        assert!((peptides[2].monoisotopic_mass - 290.1590).abs() < 1e-3);
+
+        let window = WindowParams {
+            window: 3,
+            edge_weight: 1.0,
+        };
+        let kd = &params::hydropathy::KYTE_DOOLITTLE;
+        let profile = raw.profile(kd, &window);
+        assert_eq!(profile, clean.profile(kd, &window));
+        assert_eq!(profile.values.len(), 6);
+        assert!(profile.values.iter().all(|v| !v.is_nan()));
+
+        let params = DisorderParams {
+            window: 5,
+            min_length: 1,
+            ..Default::default()
+        };
+        let prediction = raw.predict_disorder(&params);
+        assert_eq!(prediction, clean.predict_disorder(&params));
+        assert_eq!(prediction.segments, vec![0..8]);
+    }
+
+    #[test]
+    fn test_owned() {
+        let owned = ProteinSeqAnalysis::analyze(S1).into_owned();
+        assert_eq!(owned.seq, &S1[..]);
+        assert_eq!(owned.to_string(), RES1.to_string());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let json = serde_json::to_string(&*RES1).unwrap();
+        assert!(json.starts_with(r#"{"seq":"MAEGEITT"#));
+        assert!(json.contains(r#""aa_count":{"A":6,"C":3,"#));
+        assert!(json.contains(r#""half_life":{"mammalian":{"Hours":30.0},"#));
+        let owned: OwnedProteinSeqAnalysis = serde_json::from_str(&json).unwrap();
+        assert_eq!(owned, ProteinSeqAnalysis::analyze(S1).into_owned());
+        assert!(serde_json::from_str::<OwnedProteinSeqAnalysis>(
+            &json.replace(r#"{"A":6"#, r#"{"AC":6"#)
+        )
+        .is_err());
+    }
 }
@@ -0,0 +1,351 @@
+//! Readers for the AAindex database of amino acid indices.
+//!
+//! AAindex1 entries are property scales with one value per amino acid; they can be
+//! used wherever a residue scale is expected, e.g. for
+//! [profiles](../profile/index.html). AAindex2 entries are substitution or contact
+//! matrices; they implement [MatchFunc](../../../alignment/trait.MatchFunc.html),
+//! rounding each value to the nearest integer, so that they can be used for pairwise
+//! alignment. Matrices with fractional values should be scaled with a closure first.
+//!
+//! Values given as `NA` (or `-` in matrices) are missing.
+//!
+//! # Example
+//!
+//! ```
+//! use bioinformatics_algorithms::seq_analysis::protein::aaindex;
+//! let entry = b"H TEST000001
+//! D A made-up scale
+//! I    A/L     R/K     N/M     D/F     C/P     Q/S     E/T     G/W     H/Y     I/V
+//!      1.0     2.0     3.0     4.0     5.0     6.0     7.0     8.0     9.0    10.0
+//!     11.0    12.0    13.0    14.0    15.0    16.0    17.0    18.0    19.0      NA
+//! //
+//! ";
+//! let scales = aaindex::read_scales(&entry[..]).unwrap();
+//! assert_eq!(scales[0].accession, "TEST000001");
+//! assert_eq!(scales[0].values.get(&b'K'), Some(&12.0));
+//! assert_eq!(scales[0].values.get(&b'V'), None);
+//! ```
+//!
+//! # References
+//!
+//! - [Kawashima S. _et al_ (2008) AAindex: amino acid index database, progress report 2008. _Nucleic Acids Res._ **36**: D202-D205.](https://doi.org/10.1093/nar/gkm998)
+//! - [AAindex format description](https://www.genome.jp/aaindex/aaindex_help.html)
+
+use crate::alignment::msa::io::invalid_data;
+use crate::alignment::pssm::alphabet_lookup;
+use crate::alignment::MatchFunc;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead};
+
+/// Order of the amino acids in AAindex1 entries
+const AAINDEX1_ORDER: &[u8; 20] = b"ARNDCQEGHILKMFPSTWYV";
+
+/// An AAindex1 entry: one value per amino acid
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Scale {
+    pub accession: String,
+    pub description: String,
+    /// Values of the amino acids, by one-letter code; missing values are omitted
+    pub values: BTreeMap<u8, f32>,
+}
+
+/// An AAindex2 entry: a value for each pair of amino acids
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    pub accession: String,
+    pub description: String,
+    /// Residues of the rows, in order
+    pub rows: Vec<u8>,
+    /// Residues of the columns, in order
+    pub cols: Vec<u8>,
+    /// `values[i][j]` is the value of `rows[i]` against `cols[j]`
+    pub values: Vec<Vec<Option<f32>>>,
+    row_lookup: [Option<usize>; 256],
+    col_lookup: [Option<usize>; 256],
+    min_score: i32,
+}
+
+impl Matrix {
+    /// Value of the pair `(a, b)`, case-insensitively. Pairs absent from the matrix are
+    /// looked up the other way round, as both orders are usually symmetric.
+    pub fn get(&self, a: u8, b: u8) -> Option<f32> {
+        self.lookup(a, b).or_else(|| self.lookup(b, a))
+    }
+
+    fn lookup(&self, a: u8, b: u8) -> Option<f32> {
+        let i = self.row_lookup[a as usize]?;
+        let j = self.col_lookup[b as usize]?;
+        self.values[i][j]
+    }
+}
+
+impl MatchFunc for Matrix {
+    /// The value rounded to the nearest integer, or the lowest score of the matrix for
+    /// unknown residues and missing values
+    fn score(&self, a: u8, b: u8) -> i32 {
+        self.get(a, b)
+            .map(|v| v.round() as i32)
+            .unwrap_or(self.min_score)
+    }
+}
+
+/// The header fields of an entry, and the lines of its data section
+struct Entry {
+    accession: String,
+    description: String,
+    /// Line starting the data section (`I` or `M`)
+    data_header: String,
+    data: Vec<String>,
+}
+
+/// Read entries up to the `//` terminator. Continuation lines of header fields are
+/// joined to the field.
+fn read_entries<R: BufRead>(reader: R, data_tag: &str) -> io::Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    let mut entry: Option<Entry> = None;
+    let mut tag = String::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if line.starts_with("//") {
+            entries.push(entry.take().ok_or_else(|| invalid_data("empty entry"))?);
+            continue;
+        }
+        let e = entry.get_or_insert_with(|| Entry {
+            accession: String::new(),
+            description: String::new(),
+            data_header: String::new(),
+            data: Vec::new(),
+        });
+        let (field, rest) = if line.starts_with(' ') {
+            (tag.as_str(), line.trim())
+        } else {
+            let mut split = line.splitn(2, ' ');
+            tag = split.next().unwrap_or_default().to_owned();
+            (tag.as_str(), split.next().unwrap_or_default().trim())
+        };
+        match field {
+            "H" => e.accession = rest.to_owned(),
+            "D" => {
+                if !e.description.is_empty() {
+                    e.description.push(' ');
+                }
+                e.description.push_str(rest)
+            }
+            f if f == data_tag => {
+                if line.starts_with(' ') {
+                    e.data.push(rest.to_owned());
+                } else {
+                    e.data_header = rest.to_owned();
+                }
+            }
+            _ => {}
+        }
+    }
+    if entry.is_some() {
+        return Err(invalid_data("missing '//' at end of entry"));
+    }
+    Ok(entries)
+}
+
+fn parse_value(field: &str) -> io::Result<Option<f32>> {
+    match field {
+        "NA" | "-" => Ok(None),
+        _ => field
+            .parse::<f32>()
+            .map(Some)
+            .map_err(|_| invalid_data(format!("invalid value '{}'", field))),
+    }
+}
+
+fn parse_values(entry: &Entry) -> io::Result<Vec<Option<f32>>> {
+    entry
+        .data
+        .iter()
+        .flat_map(|line| line.split_whitespace())
+        .map(parse_value)
+        .collect()
+}
+
+/// Read all entries of an AAindex1 file (`aaindex1`).
+pub fn read_scales<R: BufRead>(reader: R) -> io::Result<Vec<Scale>> {
+    read_entries(reader, "I")?
+        .into_iter()
+        .map(|entry| {
+            let values = parse_values(&entry)?;
+            if values.len() != AAINDEX1_ORDER.len() {
+                return Err(invalid_data(format!(
+                    "{}: expected 20 values, found {}",
+                    entry.accession,
+                    values.len()
+                )));
+            }
+            Ok(Scale {
+                values: AAINDEX1_ORDER
+                    .iter()
+                    .zip(values)
+                    .filter_map(|(&aa, v)| Some((aa, v?)))
+                    .collect(),
+                accession: entry.accession,
+                description: entry.description,
+            })
+        })
+        .collect()
+}
+
+/// Read all entries of an AAindex2 (`aaindex2`) or AAindex3 (`aaindex3`) file. Matrices
+/// given as a lower triangle are expanded to the full symmetric matrix.
+pub fn read_matrices<R: BufRead>(reader: R) -> io::Result<Vec<Matrix>> {
+    read_entries(reader, "M")?
+        .into_iter()
+        .map(|entry| {
+            let (rows, cols) = parse_matrix_header(&entry)?;
+            let values = parse_values(&entry)?;
+            let (n, m) = (rows.len(), cols.len());
+            let mut matrix = vec![vec![None; m]; n];
+            if values.len() == n * m {
+                for (k, v) in values.into_iter().enumerate() {
+                    matrix[k / m][k % m] = v;
+                }
+            } else if n == m && values.len() == n * (n + 1) / 2 {
+                let lower_triangle = (0..n).flat_map(|i| (0..=i).map(move |j| (i, j)));
+                for ((i, j), v) in lower_triangle.zip(values) {
+                    matrix[i][j] = v;
+                    matrix[j][i] = v;
+                }
+            } else {
+                return Err(invalid_data(format!(
+                    "{}: found {} values for a {}x{} matrix",
+                    entry.accession,
+                    values.len(),
+                    n,
+                    m
+                )));
+            }
+            let min_score = matrix
+                .iter()
+                .flatten()
+                .filter_map(|&v| v)
+                .fold(f32::INFINITY, f32::min);
+            Ok(Matrix {
+                row_lookup: alphabet_lookup(&rows),
+                col_lookup: alphabet_lookup(&cols),
+                rows,
+                cols,
+                values: matrix,
+                min_score: if min_score.is_finite() {
+                    min_score.round() as i32
+                } else {
+                    0
+                },
+                accession: entry.accession,
+                description: entry.description,
+            })
+        })
+        .collect()
+}
+
+/// Parse `rows = ARND..., cols = ARND...`
+fn parse_matrix_header(entry: &Entry) -> io::Result<(Vec<u8>, Vec<u8>)> {
+    let mut rows = None;
+    let mut cols = None;
+    for part in entry.data_header.split(',') {
+        let mut kv = part.splitn(2, '=');
+        let key = kv.next().unwrap_or_default().trim();
+        let value = kv.next().unwrap_or_default().trim().as_bytes().to_vec();
+        match key {
+            "rows" => rows = Some(value),
+            "cols" => cols = Some(value),
+            _ => {}
+        }
+    }
+    match (rows, cols) {
+        (Some(rows), Some(cols)) => Ok((rows, cols)),
+        _ => Err(invalid_data(format!(
+            "{}: invalid matrix header '{}'",
+            entry.accession, entry.data_header
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static AAINDEX1: &[u8] = b"H KYTJ820101
+D Hydropathy index (Kyte-Doolittle, 1982)
+R PMID:7108955
+A Kyte, J. and Doolittle, R.F.
+T A simple method for displaying the hydropathic character of a protein
+J J. Mol. Biol. 157, 105-132 (1982)
+C JURD980101    0.996
+I    A/L     R/K     N/M     D/F     C/P     Q/S     E/T     G/W     H/Y     I/V
+      1.8    -4.5    -3.5    -3.5     2.5    -3.5    -3.5    -0.4    -3.2     4.5
+      3.8    -3.9     1.9     2.8    -1.6    -0.8    -0.7    -0.9    -1.3     4.2
+//
+H TEST000002
+D Second
+  scale
+I    A/L     R/K     N/M     D/F     C/P     Q/S     E/T     G/W     H/Y     I/V
+       NA      0.      0.      0.      0.      0.      0.      0.      0.      0.
+       0.      0.      0.      0.      0.      0.      0.      0.      0.      1.
+//
+";
+
+    static AAINDEX2: &[u8] = b"H TEST000003
+D Toy lower-triangular matrix
+M rows = ACG, cols = ACG
+      4.
+     -1.      5.
+      0.     -2.      6.
+//
+H TEST000004
+D Toy full matrix
+M rows = AC, cols = ACD
+      1.      2.      -
+     -1.5    0.4     3.
+//
+";
+
+    #[test]
+    fn test_read_scales() {
+        let scales = read_scales(AAINDEX1).unwrap();
+        assert_eq!(scales.len(), 2);
+        assert_eq!(scales[0].accession, "KYTJ820101");
+        assert_eq!(
+            scales[0].values,
+            *crate::seq_analysis::protein::params::hydropathy::KYTE_DOOLITTLE
+        );
+        assert_eq!(scales[1].description, "Second scale");
+        assert_eq!(scales[1].values.len(), 19);
+        assert_eq!(scales[1].values.get(&b'V'), Some(&1.0));
+    }
+
+    #[test]
+    fn test_read_matrices() {
+        let matrices = read_matrices(AAINDEX2).unwrap();
+        let m = &matrices[0];
+        assert_eq!(m.rows, b"ACG".to_vec());
+        assert_eq!(m.get(b'A', b'G'), Some(0.0));
+        assert_eq!(m.get(b'c', b'g'), Some(-2.0));
+        assert_eq!(m.score(b'G', b'G'), 6);
+        assert_eq!(m.score(b'A', b'W'), -2);
+
+        let m = &matrices[1];
+        assert_eq!(m.get(b'A', b'D'), None);
+        assert_eq!(m.get(b'C', b'D'), Some(3.0));
+        // looked up as (C, D)
+        assert_eq!(m.get(b'D', b'C'), Some(3.0));
+        assert_eq!(m.score(b'C', b'A'), -2);
+        assert_eq!(m.score(b'A', b'D'), -2);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(read_scales(&b"H X\nI A/L\n 1.0 2.0\n//\n"[..]).is_err());
+        assert!(read_matrices(&b"H X\nM rows = AC, cols = AC\n 1. 2.\n//\n"[..]).is_err());
+        assert!(read_scales(&b"H X\nI A/L\n"[..]).is_err());
+    }
+}
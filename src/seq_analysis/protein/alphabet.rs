@@ -0,0 +1,172 @@
+//! Validation of protein sequences and ambiguous residue codes.
+//!
+//! Besides the 20 standard amino acids, protein sequences may contain selenocysteine
+//! (U) and pyrrolysine (O), which have their own masses and (for U) pKa, and the
+//! ambiguity codes B (D or N), Z (E or Q), J (I or L) and X (any residue). An
+//! [`AmbiguityPolicy`](enum.AmbiguityPolicy.html) decides whether the ambiguity codes
+//! are rejected, dropped, or kept and [expanded](fn.expansion.html) into the mean
+//! contributions of the residues they stand for in mass and charge calculations.
+//!
+//! # Example
+//!
+//! ```
+//! use bioinformatics_algorithms::seq_analysis::protein::alphabet::{clean, AmbiguityPolicy};
+//! assert_eq!(clean(b"mkv Bxl*\n", AmbiguityPolicy::Expand).unwrap(), b"MKVBXL");
+//! assert_eq!(clean(b"MKVBXL", AmbiguityPolicy::Ignore).unwrap(), b"MKVL");
+//! let error = clean(b"MKVBXL", AmbiguityPolicy::Reject).unwrap_err();
+//! assert_eq!((error.position, error.residue), (3, b'B'));
+//! ```
+//!
+//! # References
+//!
+//! - [IUPAC-IUB Joint Commission on Biochemical Nomenclature (1984) Nomenclature and symbolism for amino acids and peptides. _Eur. J. Biochem._ **138**: 9-37.](https://doi.org/10.1111/j.1432-1033.1984.tb07877.x)
+
+use crate::seq_analysis::protein::params::AMINO_ACIDS;
+use crate::seq_analysis::protein::AminoAcidCount;
+use crate::utils::TextSlice;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+/// Ambiguity codes
+pub const AMBIGUOUS: &[u8; 4] = b"BJXZ";
+
+/// What to do with the ambiguity codes B, Z, J and X
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AmbiguityPolicy {
+    /// Fail on the first ambiguity code
+    Reject,
+    /// Drop ambiguity codes from the sequence
+    Ignore,
+    /// Keep ambiguity codes, counting them as the mean of the residues they stand for
+    #[default]
+    Expand,
+}
+
+/// A character that is not a valid residue, or an ambiguity code under
+/// [`AmbiguityPolicy::Reject`](enum.AmbiguityPolicy.html#variant.Reject)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidResidue {
+    /// 0-based position in the input
+    pub position: usize,
+    pub residue: u8,
+}
+
+impl fmt::Display for InvalidResidue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid residue '{}' at position {}",
+            self.residue.escape_ascii(),
+            self.position
+        )
+    }
+}
+
+impl Error for InvalidResidue {}
+
+/// Whether `aa` is one of the 20 standard amino acids, selenocysteine (U) or
+/// pyrrolysine (O)
+pub fn is_residue(aa: u8) -> bool {
+    AMINO_ACIDS.contains(&aa) || aa == b'U' || aa == b'O'
+}
+
+pub fn is_ambiguous(aa: u8) -> bool {
+    AMBIGUOUS.contains(&aa)
+}
+
+/// Residues an ambiguity code stands for, with their weights: D and N for B, E and Q
+/// for Z, I and L for J, and the 20 standard amino acids in equal proportions for X
+pub fn expansion(aa: u8) -> Option<Vec<(u8, f64)>> {
+    match aa {
+        b'B' => Some(vec![(b'D', 0.5), (b'N', 0.5)]),
+        b'Z' => Some(vec![(b'E', 0.5), (b'Q', 0.5)]),
+        b'J' => Some(vec![(b'I', 0.5), (b'L', 0.5)]),
+        b'X' => Some(AMINO_ACIDS.iter().map(|&aa| (aa, 0.05)).collect()),
+        _ => None,
+    }
+}
+
+/// Residue counts with the ambiguity codes replaced by their expansions
+pub fn expand_counts(aa_count: &AminoAcidCount) -> BTreeMap<u8, f64> {
+    let mut res = BTreeMap::new();
+    for (&aa, &n) in aa_count {
+        match expansion(aa) {
+            Some(residues) => {
+                for (residue, weight) in residues {
+                    *res.entry(residue).or_insert(0.0) += weight * n as f64;
+                }
+            }
+            None => *res.entry(aa).or_insert(0.0) += n as f64,
+        }
+    }
+    res
+}
+
+/// Validate a protein sequence, returning it in upper case without whitespace and
+/// without a terminal stop codon (`*`). Ambiguity codes are handled according to
+/// `policy`; any other character is an error.
+pub fn clean(seq: TextSlice<'_>, policy: AmbiguityPolicy) -> Result<Vec<u8>, InvalidResidue> {
+    let end = seq
+        .iter()
+        .rposition(|c| !c.is_ascii_whitespace())
+        .map_or(0, |i| if seq[i] == b'*' { i } else { i + 1 });
+    let mut res = Vec::with_capacity(end);
+    for (position, &c) in seq[..end].iter().enumerate() {
+        let aa = c.to_ascii_uppercase();
+        if c.is_ascii_whitespace() {
+            continue;
+        } else if is_residue(aa) {
+            res.push(aa);
+        } else if is_ambiguous(aa) {
+            match policy {
+                AmbiguityPolicy::Reject => {
+                    return Err(InvalidResidue {
+                        position,
+                        residue: c,
+                    })
+                }
+                AmbiguityPolicy::Ignore => {}
+                AmbiguityPolicy::Expand => res.push(aa),
+            }
+        } else {
+            return Err(InvalidResidue {
+                position,
+                residue: c,
+            });
+        }
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean() {
+        assert_eq!(
+            clean(b"ACDU\tO*", AmbiguityPolicy::Reject).unwrap(),
+            b"ACDUO"
+        );
+        assert_eq!(clean(b"", AmbiguityPolicy::Reject).unwrap(), b"");
+        // a stop codon within the sequence is invalid
+        let error = clean(b"AC*DE", AmbiguityPolicy::Expand).unwrap_err();
+        assert_eq!((error.position, error.residue), (2, b'*'));
+        assert_eq!(error.to_string(), "invalid residue '*' at position 2");
+        assert!(clean(b"AC-DE", AmbiguityPolicy::Ignore).is_err());
+        assert!(clean(b"AC1DE", AmbiguityPolicy::Ignore).is_err());
+    }
+
+    #[test]
+    fn test_expand_counts() {
+        let mut count = AminoAcidCount::new();
+        count.insert(b'B', 2);
+        count.insert(b'D', 1);
+        count.insert(b'X', 4);
+        let expanded = expand_counts(&count);
+        assert_eq!(expanded[&b'D'], 1.0 + 1.0 + 0.2);
+        assert_eq!(expanded[&b'N'], 1.0 + 0.2);
+        assert!((expanded.values().sum::<f64>() - 7.0).abs() < 1e-9);
+    }
+}
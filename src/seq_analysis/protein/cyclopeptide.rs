@@ -0,0 +1,424 @@
+//! Sequencing of cyclic peptides from their mass spectra.
+//!
+//! A peptide is represented by the masses of its residues. Its _linear spectrum_ is the
+//! multiset of the masses of all its subpeptides (including the empty peptide and
+//! the whole peptide), and its _cyclospectrum_ adds the subpeptides wrapping around
+//! the ring. Sequencing finds the peptides whose cyclospectrum explains an
+//! experimental spectrum:
+//!
+//! - [`cyclopeptide_sequencing`](fn.cyclopeptide_sequencing.html) is a
+//!   branch-and-bound search for peptides whose cyclospectrum is exactly the
+//!   spectrum, growing linear peptides whose linear spectra are consistent with it;
+//! - [`leaderboard_sequencing`](fn.leaderboard_sequencing.html) tolerates missing and
+//!   false peaks, keeping only the `n` best-scoring linear peptides (and their ties)
+//!   at each step;
+//! - [`spectral_convolution`](fn.spectral_convolution.html) gives the most frequent
+//!   differences between peaks, from which
+//!   [`convolution_alphabet`](fn.convolution_alphabet.html) picks an extended
+//!   alphabet of candidate residue masses, e.g. for non-proteinogenic amino acids.
+//!
+//! Masses are real numbers, compared within a tolerance in Da. The integer masses of
+//! the textbook are exact in `f64`, and are compared with a tolerance of 0.
+//!
+//! # Example
+//!
+//! ```
+//! use bioinformatics_algorithms::seq_analysis::protein::cyclopeptide::*;
+//! let spectrum = [0.0, 113.0, 128.0, 186.0, 241.0, 299.0, 314.0, 427.0];
+//! let peptides = cyclopeptide_sequencing(&spectrum, &INTEGER_MASSES, 0.0);
+//! assert_eq!(peptides.len(), 6);
+//! assert_eq!(format_peptide(&peptides[0]), "113-128-186");
+//! assert_eq!(cyclospectrum(&peptides[0]), spectrum);
+//! ```
+//!
+//! # References
+//!
+//! - Compeau P., Pevzner P. (2015) _Bioinformatics Algorithms: An Active Learning Approach_, 2nd ed., chapter 4: How Do We Sequence Antibiotics? Active Learning Publishers.
+//! - [Ng J. _et al_ (2009) Dereplication and de novo sequencing of nonribosomal peptides. _Nat. Methods_ **6**: 596-599.](https://doi.org/10.1038/nmeth.1350)
+
+use crate::utils::TextSlice;
+use std::cmp::Reverse;
+
+/// The 18 distinct integer masses of the proteinogenic amino acid residues (I/L and
+/// K/Q have the same integer mass)
+pub const INTEGER_MASSES: [f64; 18] = [
+    57.0, 71.0, 87.0, 97.0, 99.0, 101.0, 103.0, 113.0, 114.0, 115.0, 128.0, 129.0, 131.0, 137.0,
+    147.0, 156.0, 163.0, 186.0,
+];
+
+/// Integer mass of an amino acid residue
+pub fn integer_mass(aa: u8) -> Option<u32> {
+    match aa.to_ascii_uppercase() {
+        b'G' => Some(57),
+        b'A' => Some(71),
+        b'S' => Some(87),
+        b'P' => Some(97),
+        b'V' => Some(99),
+        b'T' => Some(101),
+        b'C' => Some(103),
+        b'I' | b'L' => Some(113),
+        b'N' => Some(114),
+        b'D' => Some(115),
+        b'K' | b'Q' => Some(128),
+        b'E' => Some(129),
+        b'M' => Some(131),
+        b'H' => Some(137),
+        b'F' => Some(147),
+        b'R' => Some(156),
+        b'Y' => Some(163),
+        b'W' => Some(186),
+        _ => None,
+    }
+}
+
+/// Integer masses of the residues of `seq`, or `None` if it has an unknown residue
+pub fn integer_masses(seq: TextSlice<'_>) -> Option<Vec<f64>> {
+    seq.iter()
+        .map(|&aa| integer_mass(aa).map(|m| m as f64))
+        .collect()
+}
+
+/// Write a peptide in the notation of the textbook, e.g. `113-128-186`
+pub fn format_peptide(peptide: &[f64]) -> String {
+    peptide
+        .iter()
+        .map(|m| m.to_string())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn prefix_masses(peptide: &[f64]) -> Vec<f64> {
+    let mut prefix = Vec::with_capacity(peptide.len() + 1);
+    prefix.push(0.0);
+    for (i, m) in peptide.iter().enumerate() {
+        prefix.push(prefix[i] + m);
+    }
+    prefix
+}
+
+fn sort(spectrum: &mut [f64]) {
+    spectrum.sort_by(|a, b| a.partial_cmp(b).unwrap());
+}
+
+/// Sorted masses of the empty peptide and of all the linear subpeptides of `peptide`
+pub fn linear_spectrum(peptide: &[f64]) -> Vec<f64> {
+    let prefix = prefix_masses(peptide);
+    let mut spectrum = vec![0.0];
+    for i in 0..peptide.len() {
+        for j in i + 1..=peptide.len() {
+            spectrum.push(prefix[j] - prefix[i]);
+        }
+    }
+    sort(&mut spectrum);
+    spectrum
+}
+
+/// Sorted masses of the empty peptide and of all the subpeptides of the cyclic
+/// `peptide`
+pub fn cyclospectrum(peptide: &[f64]) -> Vec<f64> {
+    let prefix = prefix_masses(peptide);
+    let total = prefix[peptide.len()];
+    let mut spectrum = vec![0.0];
+    for i in 0..peptide.len() {
+        for j in i + 1..=peptide.len() {
+            spectrum.push(prefix[j] - prefix[i]);
+            if i > 0 && j < peptide.len() {
+                spectrum.push(total - (prefix[j] - prefix[i]));
+            }
+        }
+    }
+    sort(&mut spectrum);
+    spectrum
+}
+
+/// Number of masses of the sorted `theoretical` spectrum matching distinct masses of
+/// the sorted `experimental` spectrum within `tolerance`
+pub fn score(theoretical: &[f64], experimental: &[f64], tolerance: f64) -> usize {
+    let mut matched = 0;
+    let mut j = 0;
+    for &mass in theoretical {
+        while j < experimental.len() && experimental[j] < mass - tolerance {
+            j += 1;
+        }
+        if j == experimental.len() {
+            break;
+        }
+        if experimental[j] <= mass + tolerance {
+            matched += 1;
+            j += 1;
+        }
+    }
+    matched
+}
+
+fn parent_mass(spectrum: &[f64]) -> f64 {
+    spectrum.iter().cloned().fold(0.0, f64::max)
+}
+
+fn sorted(spectrum: &[f64]) -> Vec<f64> {
+    let mut spectrum = spectrum.to_vec();
+    sort(&mut spectrum);
+    spectrum
+}
+
+/// Add each mass of `alphabet` to each peptide
+fn expand(peptides: &[Vec<f64>], alphabet: &[f64]) -> Vec<Vec<f64>> {
+    peptides
+        .iter()
+        .flat_map(|p| {
+            alphabet.iter().map(move |&m| {
+                let mut expanded = p.clone();
+                expanded.push(m);
+                expanded
+            })
+        })
+        .collect()
+}
+
+/// Peptides made of the masses of `alphabet` whose cyclospectrum is `spectrum`, within
+/// `tolerance`, in lexicographic order of their masses
+pub fn cyclopeptide_sequencing(
+    spectrum: &[f64],
+    alphabet: &[f64],
+    tolerance: f64,
+) -> Vec<Vec<f64>> {
+    let spectrum = sorted(spectrum);
+    let parent = parent_mass(&spectrum);
+    let mut alphabet = sorted(alphabet);
+    alphabet.dedup();
+    let mut peptides = vec![Vec::new()];
+    let mut res = Vec::new();
+    while !peptides.is_empty() {
+        peptides = expand(&peptides, &alphabet)
+            .into_iter()
+            .filter(|p| {
+                let mass: f64 = p.iter().sum();
+                if (mass - parent).abs() <= tolerance {
+                    let theoretical = cyclospectrum(p);
+                    if theoretical.len() == spectrum.len()
+                        && score(&theoretical, &spectrum, tolerance) == spectrum.len()
+                    {
+                        res.push(p.clone());
+                    }
+                    false
+                } else {
+                    let theoretical = linear_spectrum(p);
+                    mass < parent && score(&theoretical, &spectrum, tolerance) == theoretical.len()
+                }
+            })
+            .collect();
+    }
+    res
+}
+
+/// The `n` peptides of highest linear score, and those tied with the last of them
+fn trim(peptides: Vec<Vec<f64>>, spectrum: &[f64], n: usize, tolerance: f64) -> Vec<Vec<f64>> {
+    if peptides.len() <= n {
+        return peptides;
+    }
+    let mut scored: Vec<(usize, Vec<f64>)> = peptides
+        .into_iter()
+        .map(|p| (score(&linear_spectrum(&p), spectrum, tolerance), p))
+        .collect();
+    scored.sort_by_key(|(s, _)| Reverse(*s));
+    let cutoff = scored[n - 1].0;
+    scored
+        .into_iter()
+        .take_while(|(s, _)| *s >= cutoff)
+        .map(|(_, p)| p)
+        .collect()
+}
+
+/// Peptides made of the masses of `alphabet`, with the mass of the spectrum, whose
+/// cyclospectrum matches most masses of `spectrum` within `tolerance`. The search keeps
+/// the `n` best linear peptides at each step, with their ties. All the peptides of the
+/// best score are returned.
+pub fn leaderboard_sequencing(
+    spectrum: &[f64],
+    alphabet: &[f64],
+    n: usize,
+    tolerance: f64,
+) -> Vec<Vec<f64>> {
+    assert!(n > 0, "the leaderboard must not be empty");
+    let spectrum = sorted(spectrum);
+    let parent = parent_mass(&spectrum);
+    let mut alphabet = sorted(alphabet);
+    alphabet.dedup();
+    let mut leaderboard = vec![Vec::new()];
+    let mut leaders: Vec<Vec<f64>> = Vec::new();
+    let mut best = 0;
+    while !leaderboard.is_empty() {
+        let mut candidates = Vec::new();
+        for p in expand(&leaderboard, &alphabet) {
+            let mass: f64 = p.iter().sum();
+            if (mass - parent).abs() <= tolerance {
+                let s = score(&cyclospectrum(&p), &spectrum, tolerance);
+                if s > best {
+                    best = s;
+                    leaders.clear();
+                }
+                if s == best {
+                    leaders.push(p);
+                }
+            } else if mass < parent {
+                candidates.push(p);
+            }
+        }
+        leaderboard = trim(candidates, &spectrum, n, tolerance);
+    }
+    leaders
+}
+
+/// Positive differences between the masses of `spectrum`, with their multiplicities,
+/// by decreasing multiplicity then increasing mass. Differences are sorted and grouped
+/// with the following ones less than `width` Da above them, the mass of a group being
+/// its mean; use a width of 1 for integer spectra.
+pub fn spectral_convolution(spectrum: &[f64], width: f64) -> Vec<(f64, usize)> {
+    assert!(width > 0.0, "the width must be positive");
+    let mut differences = Vec::new();
+    for &a in spectrum {
+        for &b in spectrum {
+            if a - b >= width {
+                differences.push(a - b);
+            }
+        }
+    }
+    sort(&mut differences);
+    // groups as (first difference, sum, count)
+    let mut groups: Vec<(f64, f64, usize)> = Vec::new();
+    for d in differences {
+        match groups.last_mut() {
+            Some(group) if d - group.0 < width => {
+                group.1 += d;
+                group.2 += 1;
+            }
+            _ => groups.push((d, d, 1)),
+        }
+    }
+    let mut convolution: Vec<(f64, usize)> = groups
+        .into_iter()
+        .map(|(_, sum, n)| (sum / n as f64, n))
+        .collect();
+    convolution.sort_by_key(|&(_, n)| Reverse(n));
+    convolution
+}
+
+/// The `m` most frequent masses of the spectral convolution between 57 and 200 Da
+/// (the range of amino acid residue masses), with their ties
+pub fn convolution_alphabet(spectrum: &[f64], m: usize, width: f64) -> Vec<f64> {
+    let candidates: Vec<(f64, usize)> = spectral_convolution(spectrum, width)
+        .into_iter()
+        .filter(|&(mass, _)| mass >= 57.0 - width / 2.0 && mass <= 200.0 + width / 2.0)
+        .collect();
+    if candidates.is_empty() || m == 0 {
+        return Vec::new();
+    }
+    let cutoff = candidates[m.min(candidates.len()) - 1].1;
+    let alphabet: Vec<f64> = candidates
+        .into_iter()
+        .take_while(|&(_, n)| n >= cutoff)
+        .map(|(mass, _)| mass)
+        .collect();
+    sorted(&alphabet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spectrum(s: &str) -> Vec<f64> {
+        s.split_whitespace().map(|m| m.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_spectra() {
+        let leqn = integer_masses(b"LEQN").unwrap();
+        assert_eq!(
+            cyclospectrum(&leqn),
+            spectrum("0 113 114 128 129 227 242 242 257 355 356 370 371 484")
+        );
+        assert_eq!(
+            linear_spectrum(&leqn),
+            spectrum("0 113 114 128 129 242 242 257 370 371 484")
+        );
+        let theoretical = cyclospectrum(&integer_masses(b"NQEL").unwrap());
+        let experimental = spectrum("0 99 113 114 128 227 257 299 355 356 370 371 484");
+        assert_eq!(score(&theoretical, &experimental, 0.0), 11);
+    }
+
+    #[test]
+    fn test_cyclopeptide_sequencing() {
+        let peptides = cyclopeptide_sequencing(
+            &spectrum("0 113 128 186 241 299 314 427"),
+            &INTEGER_MASSES,
+            0.0,
+        );
+        let names: Vec<String> = peptides.iter().map(|p| format_peptide(p)).collect();
+        assert_eq!(
+            names,
+            vec![
+                "113-128-186",
+                "113-186-128",
+                "128-113-186",
+                "128-186-113",
+                "186-113-128",
+                "186-128-113"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_leaderboard_sequencing() {
+        // the cyclospectrum of 113-147-71-129, missing 200 and with a false peak at 300
+        let experimental = spectrum("0 71 113 129 147 218 242 260 300 313 331 347 389 460");
+        let leaders = leaderboard_sequencing(&experimental, &INTEGER_MASSES, 10, 0.0);
+        let expected = cyclospectrum(&[113.0, 147.0, 71.0, 129.0]);
+        // its rotations and reversals tie
+        assert_eq!(leaders.len(), 8);
+        assert!(leaders.contains(&vec![113.0, 147.0, 71.0, 129.0]));
+        assert!(leaders.iter().all(|p| cyclospectrum(p) == expected));
+    }
+
+    #[test]
+    fn test_spectral_convolution() {
+        let convolution = spectral_convolution(&spectrum("0 137 186 323"), 1.0);
+        assert_eq!(
+            convolution,
+            vec![(137.0, 2), (186.0, 2), (49.0, 1), (323.0, 1)]
+        );
+        assert_eq!(
+            convolution_alphabet(&spectrum("0 137 186 323"), 1, 1.0),
+            vec![137.0, 186.0]
+        );
+    }
+
+    #[test]
+    fn test_real_spectrum() {
+        // monoisotopic residue masses of G, A, W and V, with errors of up to 5 mDa
+        let peptide = [57.02146, 71.03711, 186.07931, 99.06841];
+        let noise = [0.003, -0.002, 0.005, -0.004, 0.001];
+        let experimental: Vec<f64> = cyclospectrum(&peptide)
+            .iter()
+            .enumerate()
+            .map(|(i, m)| {
+                if *m > 0.0 {
+                    m + noise[i % noise.len()]
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let alphabet = convolution_alphabet(&experimental, 4, 0.05);
+        assert_eq!(alphabet.len(), 4);
+        for (a, b) in alphabet.iter().zip(sorted(&peptide).iter()) {
+            assert!((a - b).abs() < 0.01);
+        }
+        let leaders = leaderboard_sequencing(&experimental, &alphabet, 10, 0.02);
+        assert!(!leaders.is_empty());
+        assert!(leaders
+            .iter()
+            .all(|p| score(&cyclospectrum(p), &experimental, 0.02) == experimental.len()));
+        let exact = cyclopeptide_sequencing(&experimental, &alphabet, 0.02);
+        assert_eq!(exact.len(), 8);
+    }
+}
@@ -0,0 +1,338 @@
+//! In-silico proteolytic digestion.
+//!
+//! A protease is described by a regular expression over the residues around its
+//! cleavage sites, with a capture group ending at the cleavage site. For example,
+//! trypsin cleaves after K or R unless followed by P: `([KR])[^P]`. Alternatives may
+//! each have their own group; the first group that takes part in a match gives the
+//! site. Sites are searched at every position, so that overlapping matches (e.g.
+//! `KK`) are all found.
+//!
+//! The built-in rules are those of ExPASy's PeptideCutter:
+//!
+//! | Protease                       | Rule                                                |
+//! |--------------------------------|-----------------------------------------------------|
+//! | trypsin                        | `([KR])[^P]`                                         |
+//! | Lys-C                          | `(K)`                                                |
+//! | Lys-N                          | `()K`                                                |
+//! | Arg-C                          | `(R)`                                                |
+//! | Asp-N                          | `()D`                                                |
+//! | Glu-C                          | `(E)`                                                |
+//! | chymotrypsin, high specificity | `([FY])[^P]\|(W)[^MP]`                               |
+//! | chymotrypsin, low specificity  | `([FLY])[^P]\|(W)[^MP]\|(M)[^PY]\|(H)[^DMPW]`        |
+//! | pepsin (pH > 2)                | `[^HKR][^P]([^R])[FLWY][^P]\|[^HKR][^P]([FLWY]).[^P]` |
+//! | CNBr                           | `(M)`                                                |
+//!
+//! # Example
+//!
+//! ```
+//! use bioinformatics_algorithms::seq_analysis::protein::digestion::{DigestParams, Protease};
+//! use bioinformatics_algorithms::seq_analysis::protein::ProteinSeqAnalysis;
+//! let protein = ProteinSeqAnalysis::new(b"MAKPGRSTKDEFK");
+//! let peptides = protein.digest(&Protease::trypsin(), &DigestParams::default());
+//! let seqs: Vec<&[u8]> = peptides.iter().map(|p| &*p.seq).collect();
+//! assert_eq!(seqs, vec![&b"MAKPGR"[..], b"STK", b"DEFK"]);
+//! assert_eq!((peptides[1].start, peptides[1].end), (6, 9));
+//! ```
+//!
+//! # References
+//!
+//! - [ExPASy PeptideCutter](https://web.expasy.org/peptide_cutter/)
+
+use crate::seq_analysis::protein::ProteinSeqAnalysis;
+use crate::utils::TextSlice;
+use regex::bytes::Regex;
+use std::borrow::Cow;
+
+/// A cleavage rule
+#[derive(Debug, Clone)]
+pub struct Protease {
+    pub name: String,
+    rule: Regex,
+}
+
+impl Protease {
+    /// A user-defined protease; see the [module documentation](index.html) for the
+    /// syntax of the rule.
+    pub fn from_regex(name: &str, rule: &str) -> Result<Self, regex::Error> {
+        let rule = Regex::new(rule)?;
+        if rule.captures_len() < 2 {
+            return Err(regex::Error::Syntax(
+                "the rule must have a capture group ending at the cleavage site".to_owned(),
+            ));
+        }
+        Ok(Protease {
+            name: name.to_owned(),
+            rule,
+        })
+    }
+
+    fn builtin(name: &str, rule: &str) -> Self {
+        Self::from_regex(name, rule).unwrap()
+    }
+
+    /// Trypsin, which does not cleave before proline
+    pub fn trypsin() -> Self {
+        Self::builtin("Trypsin", "([KR])[^P]")
+    }
+
+    pub fn lys_c() -> Self {
+        Self::builtin("Lys-C", "(K)")
+    }
+
+    pub fn lys_n() -> Self {
+        Self::builtin("Lys-N", "()K")
+    }
+
+    pub fn arg_c() -> Self {
+        Self::builtin("Arg-C", "(R)")
+    }
+
+    pub fn asp_n() -> Self {
+        Self::builtin("Asp-N", "()D")
+    }
+
+    pub fn glu_c() -> Self {
+        Self::builtin("Glu-C", "(E)")
+    }
+
+    pub fn chymotrypsin_high() -> Self {
+        Self::builtin("Chymotrypsin (high specificity)", "([FY])[^P]|(W)[^MP]")
+    }
+
+    pub fn chymotrypsin_low() -> Self {
+        Self::builtin(
+            "Chymotrypsin (low specificity)",
+            "([FLY])[^P]|(W)[^MP]|(M)[^PY]|(H)[^DMPW]",
+        )
+    }
+
+    /// Pepsin at pH > 2
+    pub fn pepsin() -> Self {
+        Self::builtin(
+            "Pepsin",
+            "[^HKR][^P]([^R])[FLWY][^P]|[^HKR][^P]([FLWY]).[^P]",
+        )
+    }
+
+    /// Cyanogen bromide
+    pub fn cnbr() -> Self {
+        Self::builtin("CNBr", "(M)")
+    }
+
+    /// Look up a built-in protease (case-insensitive): `trypsin`, `lys-c`, `lys-n`,
+    /// `arg-c`, `asp-n`, `glu-c`, `chymotrypsin-high`, `chymotrypsin-low`, `pepsin` or
+    /// `cnbr`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "trypsin" => Some(Self::trypsin()),
+            "lys-c" | "lysc" => Some(Self::lys_c()),
+            "lys-n" | "lysn" => Some(Self::lys_n()),
+            "arg-c" | "argc" => Some(Self::arg_c()),
+            "asp-n" | "aspn" => Some(Self::asp_n()),
+            "glu-c" | "gluc" => Some(Self::glu_c()),
+            "chymotrypsin-high" | "chymotrypsin" => Some(Self::chymotrypsin_high()),
+            "chymotrypsin-low" => Some(Self::chymotrypsin_low()),
+            "pepsin" => Some(Self::pepsin()),
+            "cnbr" => Some(Self::cnbr()),
+            _ => None,
+        }
+    }
+
+    /// Positions of the cleavage sites of `seq`, i.e. the number of residues before
+    /// each site, in increasing order. Sites at either end are left out.
+    pub fn cleavage_sites(&self, seq: TextSlice<'_>) -> Vec<usize> {
+        let mut sites = Vec::new();
+        let mut start = 0;
+        while start < seq.len() {
+            let caps = match self.rule.captures_at(seq, start) {
+                Some(caps) => caps,
+                None => break,
+            };
+            if let Some(site) = caps.iter().skip(1).flatten().next().map(|m| m.end()) {
+                if site > 0 && site < seq.len() {
+                    sites.push(site);
+                }
+            }
+            start = caps.get(0).unwrap().start() + 1;
+        }
+        sites.sort_unstable();
+        sites.dedup();
+        sites
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigestParams {
+    /// Maximum number of missed cleavages in a peptide
+    pub missed_cleavages: usize,
+    pub min_length: usize,
+    pub max_length: usize,
+}
+
+impl Default for DigestParams {
+    /// Complete digestion, peptides of any length
+    fn default() -> Self {
+        DigestParams {
+            missed_cleavages: 0,
+            min_length: 1,
+            max_length: usize::MAX,
+        }
+    }
+}
+
+/// A peptide from a digestion
+#[derive(Debug, Clone, PartialEq)]
+pub struct Peptide<'a> {
+    /// Residues of the peptide, borrowed from the protein unless the peptide was made
+    /// [`into_owned`](#method.into_owned)
+    pub seq: Cow<'a, [u8]>,
+    /// 0-based position of the first residue in the protein
+    pub start: usize,
+    /// 0-based position after the last residue in the protein
+    pub end: usize,
+    pub missed_cleavages: usize,
+    /// Monoisotopic mass of the neutral peptide, in Da, counting ambiguous residues as
+    /// the mean of the residues they stand for
+    pub monoisotopic_mass: f64,
+    /// Average mass of the neutral peptide, in Da, counting ambiguous residues as the
+    /// mean of the residues they stand for
+    pub average_mass: f64,
+}
+
+impl<'a> Peptide<'a> {
+    pub fn new(protein: TextSlice<'a>, start: usize, end: usize, missed_cleavages: usize) -> Self {
+        let seq = &protein[start..end];
+        let analysis = ProteinSeqAnalysis::new(seq);
+        Peptide {
+            seq: Cow::Borrowed(seq),
+            start,
+            end,
+            missed_cleavages,
+            monoisotopic_mass: analysis.calc_monoisotopic_mass(),
+            average_mass: analysis.calc_molecular_weight(),
+        }
+    }
+
+    /// The peptide with its own copy of its residues
+    pub fn into_owned(self) -> Peptide<'static> {
+        Peptide {
+            seq: Cow::Owned(self.seq.into_owned()),
+            start: self.start,
+            end: self.end,
+            missed_cleavages: self.missed_cleavages,
+            monoisotopic_mass: self.monoisotopic_mass,
+            average_mass: self.average_mass,
+        }
+    }
+}
+
+/// Digest `seq` with `protease`, returning the peptides ordered by start, then by
+/// number of missed cleavages.
+pub fn digest<'a>(
+    seq: TextSlice<'a>,
+    protease: &Protease,
+    params: &DigestParams,
+) -> Vec<Peptide<'a>> {
+    let mut bounds = vec![0];
+    bounds.extend(protease.cleavage_sites(seq));
+    bounds.push(seq.len());
+    let mut peptides = Vec::new();
+    for i in 0..bounds.len() - 1 {
+        for missed in 0..=params.missed_cleavages {
+            let j = i + 1 + missed;
+            if j >= bounds.len() {
+                break;
+            }
+            let len = bounds[j] - bounds[i];
+            if len >= params.min_length && len <= params.max_length {
+                peptides.push(Peptide::new(seq, bounds[i], bounds[j], missed));
+            }
+        }
+    }
+    peptides
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seq_analysis::protein::params::AMINO_ACIDS;
+
+    fn peptides<'a>(seq: &'a [u8], protease: &Protease, params: &DigestParams) -> Vec<&'a [u8]> {
+        digest(seq, protease, params)
+            .iter()
+            .map(|p| &seq[p.start..p.end])
+            .collect()
+    }
+
+    #[test]
+    fn test_trypsin() {
+        let trypsin = Protease::trypsin();
+        // no cleavage before P, and both sites of KK are found
+        assert_eq!(trypsin.cleavage_sites(b"AKPRKKAR"), vec![4, 5, 6]);
+        let params = DigestParams {
+            missed_cleavages: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            peptides(b"AKPRGKLR", &trypsin, &params),
+            vec![&b"AKPR"[..], b"AKPRGK", b"GK", b"GKLR", b"LR"]
+        );
+    }
+
+    #[test]
+    fn test_builtin_rules() {
+        let seq = b"MKDEWPFYLHDR";
+        assert_eq!(Protease::lys_c().cleavage_sites(seq), vec![2]);
+        assert_eq!(Protease::lys_n().cleavage_sites(seq), vec![1]);
+        assert_eq!(Protease::asp_n().cleavage_sites(seq), vec![2, 10]);
+        assert_eq!(Protease::glu_c().cleavage_sites(seq), vec![4]);
+        assert_eq!(Protease::arg_c().cleavage_sites(seq), Vec::<usize>::new());
+        assert_eq!(Protease::cnbr().cleavage_sites(seq), vec![1]);
+        assert_eq!(
+            Protease::chymotrypsin_high().cleavage_sites(seq),
+            vec![7, 8]
+        );
+        assert_eq!(
+            Protease::chymotrypsin_low().cleavage_sites(seq),
+            vec![1, 7, 8, 9]
+        );
+        assert!(Protease::from_name("Glu-C").is_some());
+        assert!(Protease::from_name("papain").is_none());
+    }
+
+    #[test]
+    fn test_user_defined() {
+        assert!(Protease::from_regex("no group", "K").is_err());
+        assert!(Protease::from_regex("invalid", "([K)").is_err());
+        let enterokinase = Protease::from_regex("Enterokinase", "[DE]{3}(K)").unwrap();
+        assert_eq!(enterokinase.cleavage_sites(b"GDDDDKIVGG"), vec![6]);
+    }
+
+    #[test]
+    fn test_length_and_mass() {
+        let params = DigestParams {
+            missed_cleavages: 0,
+            min_length: 3,
+            max_length: 5,
+        };
+        let peptides = digest(b"GKAAAKAAAAAAKG", &Protease::trypsin(), &params);
+        assert_eq!(peptides.len(), 1);
+        assert_eq!(&*peptides[0].seq, b"AAAK");
+        // 3 Ala + Lys residues and water
+        assert!((peptides[0].monoisotopic_mass - 359.2168).abs() < 1e-3);
+
+        // ambiguity codes count as the mean of the residues they stand for
+        let masses = |seq: &[u8]| {
+            let peptide = Peptide::new(seq, 0, seq.len(), 0);
+            (peptide.monoisotopic_mass, peptide.average_mass)
+        };
+        let mean = AMINO_ACIDS
+            .iter()
+            .map(|&aa| masses(&[b'A', aa, b'K']).0)
+            .sum::<f64>()
+            / 20.0;
+        assert!((masses(b"AXK").0 - mean).abs() < 1e-9);
+        let (d, n) = (masses(b"ADK").1, masses(b"ANK").1);
+        assert!((masses(b"ABK").1 - (d + n) / 2.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,222 @@
+//! Prediction of intrinsically disordered regions with FoldIndex.
+//!
+//! Uversky _et al_ (2000) found that natively unfolded proteins combine a low mean
+//! hydropathy `<H>`, on the Kyte & Doolittle scale normalised between 0 and 1, with a
+//! high absolute mean net charge `<R>`, and that the two classes are separated by the
+//! boundary `<R> = 2.785 <H> - 1.151`. FoldIndex (Prilusky _et al_, 2005) computes
+//! `2.785 <H> - |<R>| - 1.151` over a sliding window: residues whose score is negative
+//! are predicted to be disordered, positive ones to be folded.
+//!
+//! # Example
+//!
+//! ```
+//! use bioinformatics_algorithms::seq_analysis::protein::disorder::{fold_index, predict, DisorderParams};
+//! // a hydrophobic domain followed by a charged, proline-rich tail
+//! let domain = b"MVLSAIWLAGFVALCYIAGTLSVIPLVFAAGLTVAWIGCLNFVAALGSIVAGFLYLVAKG";
+//! let tail = b"SEEKPEEKKPESEEPKKEEGSEPKEDEKKPESPEEKKSDEPKEEKPSEE";
+//! let seq = [&domain[..], tail].concat();
+//! assert!(fold_index(domain).unwrap() > 0.0 && fold_index(tail).unwrap() < 0.0);
+//! let prediction = predict(&seq, &DisorderParams::default());
+//! assert_eq!(prediction.segments.len(), 1);
+//! assert_eq!(prediction.segments[0].end, seq.len());
+//! ```
+//!
+//! # References
+//!
+//! - [Uversky V.N., Gillespie J.R., Fink A.L. (2000) Why are "natively unfolded" proteins unstructured under physiologic conditions? _Proteins_ **41**: 415-427.](https://doi.org/10.1002/1097-0134(20001115)41:3<415::AID-PROT130>3.0.CO;2-7)
+//! - [Prilusky J., Felder C.E., Zeev-Ben-Mordehai T., Rydberg E.H., Man O., Beckmann J.S., Silman I., Sussman J.L. (2005) FoldIndex: a simple tool to predict whether a given protein sequence is intrinsically unfolded. _Bioinformatics_ **21**: 3435-3438.](https://doi.org/10.1093/bioinformatics/bti537)
+
+use crate::seq_analysis::protein::params::hydropathy::KYTE_DOOLITTLE;
+use crate::seq_analysis::protein::profile::{profile, Profile, WindowParams};
+use crate::utils::TextSlice;
+use lazy_static::lazy_static;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+lazy_static! {
+    /// Kyte & Doolittle hydropathy, rescaled from [-4.5, 4.5] to [0, 1]
+    pub static ref NORMALIZED_HYDROPATHY: BTreeMap<u8, f32> = KYTE_DOOLITTLE
+        .iter()
+        .map(|(&aa, &h)| (aa, (h + 4.5) / 9.0))
+        .collect();
+
+    /// Charge of each residue at neutral pH, +1 for Lys and Arg and -1 for Asp and Glu
+    pub static ref NET_CHARGE: BTreeMap<u8, f32> = KYTE_DOOLITTLE
+        .keys()
+        .map(|&aa| {
+            let charge = match aa {
+                b'K' | b'R' => 1.0,
+                b'D' | b'E' => -1.0,
+                _ => 0.0,
+            };
+            (aa, charge)
+        })
+        .collect();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DisorderParams {
+    /// Number of residues in the window; must be odd. Sequences shorter than the window
+    /// are scored with a single window as long as possible.
+    pub window: usize,
+    /// Residues scoring below the threshold are disordered
+    pub threshold: f32,
+    /// Minimum number of consecutive disordered residues in a reported segment
+    pub min_length: usize,
+}
+
+impl Default for DisorderParams {
+    /// The window of 51 residues of the FoldIndex server, a threshold of 0, and
+    /// segments of at least 10 residues
+    fn default() -> Self {
+        DisorderParams {
+            window: 51,
+            threshold: 0.0,
+            min_length: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DisorderPrediction {
+    /// FoldIndex of the residues, negative for disordered ones
+    pub scores: Profile,
+    /// 0-based, half-open ranges of the disordered segments, in order. Segments
+    /// reaching the first or last scored residue are extended to the end of the
+    /// sequence.
+    pub segments: Vec<Range<usize>>,
+}
+
+impl DisorderPrediction {
+    /// Fraction of the residues of a sequence of length `len` in disordered segments
+    pub fn disordered_fraction(&self, len: usize) -> f64 {
+        let disordered: usize = self.segments.iter().map(|s| s.len()).sum();
+        disordered as f64 / len as f64
+    }
+}
+
+fn score(hydropathy: f32, charge: f32) -> f32 {
+    2.785 * hydropathy - charge.abs() - 1.151
+}
+
+/// FoldIndex of a whole sequence, negative if it is predicted to be natively unfolded.
+/// Residues other than the 20 standard amino acids, in upper case, are left out;
+/// `None` if there are none.
+pub fn fold_index(seq: TextSlice<'_>) -> Option<f32> {
+    let residues: Vec<u8> = seq
+        .iter()
+        .filter(|aa| NORMALIZED_HYDROPATHY.contains_key(aa))
+        .cloned()
+        .collect();
+    if residues.is_empty() {
+        return None;
+    }
+    let mean = |scale: &BTreeMap<u8, f32>| {
+        residues.iter().map(|aa| scale[aa]).sum::<f32>() / residues.len() as f32
+    };
+    Some(score(mean(&NORMALIZED_HYDROPATHY), mean(&NET_CHARGE)))
+}
+
+/// Per-residue FoldIndex of `seq` and its disordered segments
+pub fn predict(seq: TextSlice<'_>, params: &DisorderParams) -> DisorderPrediction {
+    assert!(params.window % 2 == 1, "window size must be odd");
+    if seq.is_empty() {
+        return DisorderPrediction::default();
+    }
+    let window = WindowParams {
+        window: params.window.min(seq.len() - (1 - seq.len() % 2)),
+        edge_weight: 1.0,
+    };
+    let hydropathy = profile(seq, &NORMALIZED_HYDROPATHY, &window);
+    let charge = profile(seq, &NET_CHARGE, &window);
+    let scores = Profile {
+        offset: hydropathy.offset,
+        values: hydropathy
+            .values
+            .iter()
+            .zip(&charge.values)
+            .map(|(&h, &r)| score(h, r))
+            .collect(),
+    };
+
+    let last = scores.offset + scores.values.len() - 1;
+    let mut segments = Vec::new();
+    let mut start = None;
+    for (pos, value) in scores.iter() {
+        match (value < params.threshold, start) {
+            (true, None) => start = Some(pos),
+            (false, Some(s)) => {
+                segments.push(s..pos);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        segments.push(s..last + 1);
+    }
+    let segments = segments
+        .into_iter()
+        .map(|s| {
+            let start = if s.start == scores.offset { 0 } else { s.start };
+            let end = if s.end == last + 1 { seq.len() } else { s.end };
+            start..end
+        })
+        .filter(|s| s.len() >= params.min_length)
+        .collect();
+    DisorderPrediction { scores, segments }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold_index() {
+        assert!((fold_index(b"IIII").unwrap() - 1.634).abs() < 1e-5);
+        // <H> = 1/9, |<R>| = 1
+        assert!((fold_index(b"EEEE").unwrap() - (2.785 / 9.0 - 2.151)).abs() < 1e-5);
+        // charges of opposite signs cancel out: <H> = 0.8 / 9, <R> = 0
+        assert!((fold_index(b"EKEK").unwrap() - (2.785 * 0.8 / 9.0 - 1.151)).abs() < 1e-5);
+        assert_eq!(fold_index(b"EXKE*K"), fold_index(b"EKEK"));
+        // no standard residues
+        assert_eq!(fold_index(b""), None);
+        assert_eq!(fold_index(b"ekek"), None);
+        assert_eq!(fold_index(b"XB*"), None);
+    }
+
+    #[test]
+    fn test_predict() {
+        let folded = b"MVLSAIWLAGFVALCYIAGTLSVIPLVFAAGLTVAWIGCLNFVAALGSIVAGFLYLVAKG";
+        let tail = b"SEEKPEEKKPESEEPKKEEGSEPKEDEKKPESPEEKKSDEPKEEKPSEE";
+        let seq = [&tail[..], folded, tail].concat();
+        let params = DisorderParams {
+            window: 21,
+            ..Default::default()
+        };
+        let prediction = predict(&seq, &params);
+        assert_eq!(prediction.scores.offset, 10);
+        assert_eq!(prediction.scores.values.len(), seq.len() - 20);
+        assert_eq!(prediction.segments.len(), 2);
+        assert_eq!(prediction.segments[0].start, 0);
+        assert_eq!(prediction.segments[1].end, seq.len());
+        let (n_end, c_start) = (prediction.segments[0].end, prediction.segments[1].start);
+        assert!((tail.len()..tail.len() + 10).contains(&n_end));
+        assert!((tail.len() + folded.len() - 10..=tail.len() + folded.len()).contains(&c_start));
+
+        let params = DisorderParams {
+            min_length: 100,
+            ..params
+        };
+        assert!(predict(&seq, &params).segments.is_empty());
+    }
+
+    #[test]
+    fn test_short() {
+        let prediction = predict(b"SEEKPEEKKPESEE", &DisorderParams::default());
+        assert_eq!(prediction.scores.values.len(), 2);
+        assert_eq!(prediction.segments, vec![0..14]);
+        assert_eq!(prediction.disordered_fraction(14), 1.0);
+        assert!(predict(b"", &DisorderParams::default()).segments.is_empty());
+    }
+}
@@ -0,0 +1,368 @@
+//! Theoretical fragment ions of peptides, for the annotation of MS/MS spectra.
+//!
+//! Ions are named after Roepstorff and Fohlman, as modified by Biemann: `a`, `b` and
+//! `c` ions contain the N-terminus of the peptide, `x`, `y` and `z` ions its
+//! C-terminus, and the number of an ion is its number of residues. With `M` the sum
+//! of the monoisotopic masses of its residues (and of their modifications), the
+//! neutral masses of the ions are:
+//!
+//! | Ion | Mass             |
+//! |-----|------------------|
+//! | a   | `M - CO`         |
+//! | b   | `M`              |
+//! | c   | `M + NH3`        |
+//! | x   | `M + H2O + CO - H2` |
+//! | y   | `M + H2O`        |
+//! | z   | `M + H2O - NH3`  |
+//!
+//! and an ion of charge `z` has an m/z of `(mass + z * proton) / z`. The `z•` radical
+//! ions seen in ETD spectra are one hydrogen atom heavier than `z` ions.
+//!
+//! Neutral losses of water are considered for fragments containing S, T, E or D,
+//! and of ammonia for fragments containing R, K, N or Q.
+//!
+//! # Example
+//!
+//! ```
+//! use bioinformatics_algorithms::seq_analysis::protein::fragmentation::*;
+//! let ions = fragment_ions(b"PEPTIDE", &ModificationMasses::default(), &FragmentParams::default())
+//!     .unwrap();
+//! let y1 = ions.iter().find(|ion| ion.label() == "y1" && ion.charge == 1).unwrap();
+//! assert!((y1.mz - 148.0604).abs() < 1e-3);
+//! let b2 = ions.iter().find(|ion| ion.label() == "b2" && ion.charge == 1).unwrap();
+//! assert!((b2.mz - 227.1026).abs() < 1e-3);
+//! ```
+//!
+//! # References
+//!
+//! - [Roepstorff P., Fohlman J. (1984) Proposal for a common nomenclature for sequence ions in mass spectra of peptides. _Biomed. Mass Spectrom._ **11**: 601.](https://doi.org/10.1002/bms.1200111109)
+//! - [Biemann K. (1990) Appendix 5. Nomenclature for peptide fragment ions (positive ions). _Methods Enzymol._ **193**: 886-887.](https://doi.org/10.1016/0076-6879(90)93460-3)
+
+use crate::seq_analysis::protein::alphabet::InvalidResidue;
+use crate::seq_analysis::protein::mass::{Element, PROTON_MASS, RESIDUE_FORMULAS, WATER};
+use crate::utils::TextSlice;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Write};
+
+fn ammonia() -> f64 {
+    Element::N.monoisotopic_mass() + 3.0 * Element::H.monoisotopic_mass()
+}
+
+fn carbon_monoxide() -> f64 {
+    Element::C.monoisotopic_mass() + Element::O.monoisotopic_mass()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IonType {
+    A,
+    B,
+    C,
+    X,
+    Y,
+    Z,
+}
+
+impl IonType {
+    /// Whether the ion contains the N-terminus of the peptide
+    pub fn is_n_terminal(self) -> bool {
+        match self {
+            IonType::A | IonType::B | IonType::C => true,
+            IonType::X | IonType::Y | IonType::Z => false,
+        }
+    }
+
+    /// Mass to add to the sum of the residue masses to get the neutral mass of the
+    /// ion, in Da
+    pub fn mass_offset(self) -> f64 {
+        let water = WATER.monoisotopic_mass();
+        match self {
+            IonType::A => -carbon_monoxide(),
+            IonType::B => 0.0,
+            IonType::C => ammonia(),
+            IonType::X => water + carbon_monoxide() - 2.0 * Element::H.monoisotopic_mass(),
+            IonType::Y => water,
+            IonType::Z => water - ammonia(),
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            IonType::A => 'a',
+            IonType::B => 'b',
+            IonType::C => 'c',
+            IonType::X => 'x',
+            IonType::Y => 'y',
+            IonType::Z => 'z',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NeutralLoss {
+    Water,
+    Ammonia,
+}
+
+impl NeutralLoss {
+    pub fn mass(self) -> f64 {
+        match self {
+            NeutralLoss::Water => WATER.monoisotopic_mass(),
+            NeutralLoss::Ammonia => ammonia(),
+        }
+    }
+
+    /// Residues which can lose this neutral
+    pub fn residues(self) -> &'static [u8] {
+        match self {
+            NeutralLoss::Water => b"STED",
+            NeutralLoss::Ammonia => b"RKNQ",
+        }
+    }
+
+    pub fn formula(self) -> &'static str {
+        match self {
+            NeutralLoss::Water => "H2O",
+            NeutralLoss::Ammonia => "NH3",
+        }
+    }
+}
+
+/// Mass deltas of the modifications of a peptide, in Da
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModificationMasses {
+    /// Fixed modifications, applied to every occurrence of a residue (e.g.
+    /// carbamidomethylation of C, +57.021464)
+    pub fixed: BTreeMap<u8, f64>,
+    /// Variable modifications present on this peptide, by 0-based position
+    pub variable: BTreeMap<usize, f64>,
+    pub n_term: f64,
+    pub c_term: f64,
+}
+
+impl ModificationMasses {
+    /// Monoisotopic mass of each residue of `seq`, including its modifications, or an
+    /// error for the first residue without a known mass, such as an ambiguity code or
+    /// a lowercase letter
+    pub fn residue_masses(&self, seq: TextSlice<'_>) -> Result<Vec<f64>, InvalidResidue> {
+        seq.iter()
+            .enumerate()
+            .map(|(i, aa)| {
+                let residue = RESIDUE_FORMULAS.get(aa).ok_or(InvalidResidue {
+                    position: i,
+                    residue: *aa,
+                })?;
+                Ok(residue.monoisotopic_mass()
+                    + self.fixed.get(aa).cloned().unwrap_or(0.0)
+                    + self.variable.get(&i).cloned().unwrap_or(0.0))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FragmentParams {
+    pub ion_types: Vec<IonType>,
+    /// Ions are computed at charges `1..=max_charge`
+    pub max_charge: u8,
+    pub neutral_losses: Vec<NeutralLoss>,
+}
+
+impl Default for FragmentParams {
+    /// b and y ions, singly and doubly charged, with losses of water and ammonia
+    fn default() -> Self {
+        FragmentParams {
+            ion_types: vec![IonType::B, IonType::Y],
+            max_charge: 2,
+            neutral_losses: vec![NeutralLoss::Water, NeutralLoss::Ammonia],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FragmentIon {
+    pub ion_type: IonType,
+    /// Number of residues in the fragment
+    pub number: usize,
+    pub charge: u8,
+    pub loss: Option<NeutralLoss>,
+    pub mz: f64,
+}
+
+impl FragmentIon {
+    /// Name of the ion without its charge, e.g. `y7` or `b3-H2O`
+    pub fn label(&self) -> String {
+        match self.loss {
+            Some(loss) => format!(
+                "{}{}-{}",
+                self.ion_type.to_char(),
+                self.number,
+                loss.formula()
+            ),
+            None => format!("{}{}", self.ion_type.to_char(), self.number),
+        }
+    }
+}
+
+impl fmt::Display for FragmentIon {
+    /// The label followed by the charge as `+` signs, e.g. `y7++`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.label(), "+".repeat(self.charge as usize))
+    }
+}
+
+/// Fragment ions of the peptide `seq` with modifications `mods`, grouped by ion type,
+/// charge and neutral loss, by increasing number. Fragments of 1 to `n - 1` residues
+/// are computed, `n` being the length of the peptide. Fails if `seq` contains a
+/// residue without a known mass.
+pub fn fragment_ions(
+    seq: TextSlice<'_>,
+    mods: &ModificationMasses,
+    params: &FragmentParams,
+) -> Result<Vec<FragmentIon>, InvalidResidue> {
+    assert!(params.max_charge > 0, "the maximum charge must be positive");
+    let masses = mods.residue_masses(seq)?;
+    let mut ions = Vec::new();
+    for &ion_type in &params.ion_types {
+        let residues = |number: usize| {
+            if ion_type.is_n_terminal() {
+                0..number
+            } else {
+                seq.len() - number..seq.len()
+            }
+        };
+        let terminus = if ion_type.is_n_terminal() {
+            mods.n_term
+        } else {
+            mods.c_term
+        };
+        for charge in 1..=params.max_charge {
+            let losses = std::iter::once(None).chain(params.neutral_losses.iter().map(Some));
+            for loss in losses {
+                for number in 1..seq.len() {
+                    let range = residues(number);
+                    let mut mass = masses[range.clone()].iter().sum::<f64>()
+                        + terminus
+                        + ion_type.mass_offset();
+                    if let Some(loss) = loss {
+                        if !seq[range].iter().any(|aa| loss.residues().contains(aa)) {
+                            continue;
+                        }
+                        mass -= loss.mass();
+                    }
+                    ions.push(FragmentIon {
+                        ion_type,
+                        number,
+                        charge,
+                        loss: loss.cloned(),
+                        mz: (mass + charge as f64 * PROTON_MASS) / charge as f64,
+                    });
+                }
+            }
+        }
+    }
+    Ok(ions)
+}
+
+/// Write `ions` as a tab-separated table with columns `ion`, `charge` and `mz`
+pub fn write_table<W: Write>(ions: &[FragmentIon], mut writer: W) -> io::Result<()> {
+    writeln!(writer, "ion\tcharge\tmz")?;
+    for ion in ions {
+        writeln!(writer, "{}\t{}\t{:.5}", ion.label(), ion.charge, ion.mz)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find<'a>(ions: &'a [FragmentIon], label: &str, charge: u8) -> Option<&'a FragmentIon> {
+        ions.iter()
+            .find(|ion| ion.label() == label && ion.charge == charge)
+    }
+
+    #[test]
+    fn test_ion_types() {
+        let params = FragmentParams {
+            ion_types: vec![
+                IonType::A,
+                IonType::B,
+                IonType::C,
+                IonType::X,
+                IonType::Y,
+                IonType::Z,
+            ],
+            max_charge: 2,
+            neutral_losses: vec![],
+        };
+        let ions = fragment_ions(b"PEPTIDE", &ModificationMasses::default(), &params).unwrap();
+        assert_eq!(ions.len(), 6 * 2 * 6);
+        let mz = |label, charge| find(&ions, label, charge).unwrap().mz;
+        assert!((mz("b2", 1) - 227.10263).abs() < 1e-4);
+        assert!((mz("a2", 1) - 199.10771).abs() < 1e-4);
+        assert!((mz("c2", 1) - 244.12918).abs() < 1e-4);
+        assert!((mz("y1", 1) - 148.06043).abs() < 1e-4);
+        assert!((mz("x1", 1) - 174.03969).abs() < 1e-4);
+        assert!((mz("z1", 1) - 131.03388).abs() < 1e-4);
+        assert!((mz("y2", 2) - (mz("y2", 1) + PROTON_MASS) / 2.0).abs() < 1e-9);
+        // complementary b and y ions add up to the [M+H]+ precursor plus a proton
+        let precursor = mz("b3", 1) + mz("y4", 1) - PROTON_MASS;
+        assert!((precursor - 800.3673).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_neutral_losses() {
+        let params = FragmentParams {
+            ion_types: vec![IonType::B],
+            max_charge: 1,
+            neutral_losses: vec![NeutralLoss::Water, NeutralLoss::Ammonia],
+        };
+        let ions = fragment_ions(b"GASK", &ModificationMasses::default(), &params).unwrap();
+        // b1 and b2 contain neither S nor K
+        assert!(find(&ions, "b2-H2O", 1).is_none());
+        let b3 = find(&ions, "b3", 1).unwrap().mz;
+        let b3_water = find(&ions, "b3-H2O", 1).unwrap().mz;
+        assert!((b3 - b3_water - 18.01056).abs() < 1e-4);
+        assert!(find(&ions, "b3-NH3", 1).is_none());
+        assert_eq!(ions.len(), 4);
+    }
+
+    #[test]
+    fn test_modifications() {
+        let mut mods = ModificationMasses::default();
+        mods.fixed.insert(b'C', 57.021464);
+        mods.variable.insert(0, 79.966331);
+        mods.c_term = -0.984016;
+        let params = FragmentParams {
+            neutral_losses: vec![],
+            max_charge: 1,
+            ..Default::default()
+        };
+        let plain = fragment_ions(b"SCAK", &ModificationMasses::default(), &params).unwrap();
+        let modified = fragment_ions(b"SCAK", &mods, &params).unwrap();
+        let delta =
+            |label| find(&modified, label, 1).unwrap().mz - find(&plain, label, 1).unwrap().mz;
+        assert!((delta("b1") - 79.966331).abs() < 1e-9);
+        assert!((delta("b2") - 79.966331 - 57.021464).abs() < 1e-9);
+        assert!((delta("y2") + 0.984016).abs() < 1e-9);
+        assert!((delta("y3") - 57.021464 + 0.984016).abs() < 1e-9);
+
+        let mut table = Vec::new();
+        write_table(&modified[..1], &mut table).unwrap();
+        assert_eq!(
+            String::from_utf8(table).unwrap(),
+            format!("ion\tcharge\tmz\nb1\t1\t{:.5}\n", modified[0].mz)
+        );
+        assert_eq!(modified[0].to_string(), "b1+");
+    }
+
+    #[test]
+    fn test_unknown_residue() {
+        let params = FragmentParams::default();
+        let mods = ModificationMasses::default();
+        let error = fragment_ions(b"PEPXIDE", &mods, &params).unwrap_err();
+        assert_eq!((error.position, error.residue), (3, b'X'));
+        assert!(fragment_ions(b"peptide", &mods, &params).is_err());
+    }
+}
@@ -0,0 +1,287 @@
+//! Hydrophobic moment, amphipathic segments and helical wheel projections.
+//!
+//! The hydrophobic moment of Eisenberg _et al_ is the vector sum of the
+//! hydrophobicities `h_i` of the residues, each pointing in the direction `i * δ` of
+//! its side chain around the axis of the secondary structure element:
+//! `μH = |Σ h_i (cos(iδ), sin(iδ))|`. The angle `δ` is 100° for an α-helix and
+//! 160–180° for a β-strand. The functions below report the mean moment per residue,
+//! `μH / N`, so that windows of different sizes are comparable.
+//!
+//! # Example
+//!
+//! ```
+//! use bioinformatics_algorithms::seq_analysis::protein::hydrophobic_moment::*;
+//! use bioinformatics_algorithms::seq_analysis::protein::params::hydropathy::EISENBERG;
+//! // magainin 2, an amphipathic antimicrobial peptide
+//! let magainin = b"GIGKFLHSAKKFGKAFVGEIMNS";
+//! let moment = hydrophobic_moment(magainin, &EISENBERG, ALPHA_HELIX);
+//! assert!(moment > 0.2);
+//! let mut wheel = Vec::new();
+//! write_helical_wheel_text(&magainin[..18], ALPHA_HELIX, &mut wheel).unwrap();
+//! assert_eq!(wheel.split(|&c| c == b'\n').filter(|l| !l.is_empty()).count(), 18);
+//! ```
+//!
+//! # References
+//!
+//! - [Eisenberg D., Weiss R.M., Terwilliger T.C. (1982) The helical hydrophobic moment: a measure of the amphiphilicity of a helix. _Nature_ **299**: 371-374.](https://doi.org/10.1038/299371a0)
+//! - [Eisenberg D., Schwarz E., Komaromy M., Wall R. (1984) Analysis of membrane and surface protein sequences with the hydrophobic moment plot. _J. Mol. Biol._ **179**: 125-142.](https://doi.org/10.1016/0022-2836(84)90309-7)
+
+use crate::seq_analysis::protein::profile::Profile;
+use crate::utils::TextSlice;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::ops::Range;
+
+/// Angle between consecutive residues of an α-helix, in degrees
+pub const ALPHA_HELIX: f32 = 100.0;
+/// Angle between consecutive residues of a β-strand, in degrees
+pub const BETA_STRAND: f32 = 170.0;
+
+/// Sums of the hydrophobicity vectors of `seq`, as `(x, y)`
+fn moment_vector(seq: TextSlice<'_>, scale: &BTreeMap<u8, f32>, angle: f32) -> (f32, f32) {
+    let delta = angle.to_radians();
+    seq.iter()
+        .enumerate()
+        .fold((0.0, 0.0), |(x, y), (i, aa)| match scale.get(aa) {
+            Some(h) => {
+                let theta = i as f32 * delta;
+                (x + h * theta.cos(), y + h * theta.sin())
+            }
+            None => (x, y),
+        })
+}
+
+/// Mean hydrophobic moment per residue of `seq` at `angle` degrees. Residues
+/// missing from the scale count as 0.
+pub fn hydrophobic_moment(seq: TextSlice<'_>, scale: &BTreeMap<u8, f32>, angle: f32) -> f32 {
+    if seq.is_empty() {
+        return 0.0;
+    }
+    let (x, y) = moment_vector(seq, scale, angle);
+    x.hypot(y) / seq.len() as f32
+}
+
+/// Mean hydrophobicity per residue of `seq`. Residues missing from the scale count
+/// as 0.
+pub fn mean_hydrophobicity(seq: TextSlice<'_>, scale: &BTreeMap<u8, f32>) -> f32 {
+    if seq.is_empty() {
+        return 0.0;
+    }
+    seq.iter().filter_map(|aa| scale.get(aa)).sum::<f32>() / seq.len() as f32
+}
+
+/// Mean hydrophobic moment of each window of `window` residues, assigned to the
+/// centre of the window
+pub fn moment_profile(
+    seq: TextSlice<'_>,
+    scale: &BTreeMap<u8, f32>,
+    window: usize,
+    angle: f32,
+) -> Profile {
+    assert!(window % 2 == 1, "window size must be odd");
+    Profile {
+        offset: window / 2,
+        values: seq
+            .windows(window)
+            .map(|w| hydrophobic_moment(w, scale, angle))
+            .collect(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AmphipathicParams {
+    /// Number of residues in a window
+    pub window: usize,
+    /// Angle between consecutive residues, in degrees
+    pub angle: f32,
+    /// Minimum mean hydrophobic moment of an amphipathic window
+    pub min_moment: f32,
+}
+
+impl Default for AmphipathicParams {
+    /// Windows of 11 residues in an α-helix, as in the hydrophobic moment plot, with
+    /// a moment threshold suited to the normalised consensus scale
+    fn default() -> Self {
+        AmphipathicParams {
+            window: 11,
+            angle: ALPHA_HELIX,
+            min_moment: 0.5,
+        }
+    }
+}
+
+/// Segments covered by overlapping windows whose mean hydrophobic moment reaches
+/// `params.min_moment`, as 0-based half-open ranges
+pub fn amphipathic_segments(
+    seq: TextSlice<'_>,
+    scale: &BTreeMap<u8, f32>,
+    params: &AmphipathicParams,
+) -> Vec<Range<usize>> {
+    let mut segments: Vec<Range<usize>> = Vec::new();
+    if seq.len() < params.window {
+        return segments;
+    }
+    for start in 0..=seq.len() - params.window {
+        let end = start + params.window;
+        if hydrophobic_moment(&seq[start..end], scale, params.angle) >= params.min_moment {
+            match segments.last_mut() {
+                Some(last) if last.end >= start => last.end = end,
+                _ => segments.push(start..end),
+            }
+        }
+    }
+    segments
+}
+
+/// Positions of the residues on a helical wheel: the angle of each spoke in degrees,
+/// in `[0, 360)` and increasing order, with the 0-based positions of its residues
+pub fn helical_wheel(seq: TextSlice<'_>, angle: f32) -> Vec<(f32, Vec<usize>)> {
+    // group residues by angle rounded to 0.01°, so that float errors don't split spokes
+    let mut spokes: BTreeMap<i64, Vec<usize>> = BTreeMap::new();
+    for i in 0..seq.len() {
+        let theta = (i as f64 * angle as f64).rem_euclid(360.0);
+        let key = (theta * 100.0).round() as i64 % 36000;
+        spokes.entry(key).or_default().push(i);
+    }
+    spokes
+        .into_iter()
+        .map(|(key, residues)| (key as f32 / 100.0, residues))
+        .collect()
+}
+
+/// Write the helical wheel as text, one spoke per line, e.g. `100° K4 F14`, with
+/// 1-based residue numbers.
+pub fn write_helical_wheel_text<W: Write>(
+    seq: TextSlice<'_>,
+    angle: f32,
+    mut writer: W,
+) -> io::Result<()> {
+    for (theta, residues) in helical_wheel(seq, angle) {
+        write!(writer, "{:>6.1}°", theta)?;
+        for i in residues {
+            write!(writer, " {}{}", seq[i] as char, i + 1)?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// Write the helical wheel as SVG. Residues are drawn on concentric circles, one per
+/// turn of the wheel, coloured by their value on `scale` (hydrophobic in orange,
+/// hydrophilic in blue); an arrow shows the direction of the hydrophobic moment.
+pub fn write_helical_wheel_svg<W: Write>(
+    seq: TextSlice<'_>,
+    scale: &BTreeMap<u8, f32>,
+    angle: f32,
+    mut writer: W,
+) -> io::Result<()> {
+    const SIZE: f32 = 400.0;
+    const RADIUS: f32 = 14.0;
+    let spokes = helical_wheel(seq, angle).len().max(1);
+    let turns = seq.len().div_ceil(spokes);
+    let ring = |turn: usize| 60.0 + 2.2 * RADIUS * turn as f32;
+    let size = SIZE.max(2.0 * (ring(turns.saturating_sub(1)) + 2.0 * RADIUS));
+    let centre = size / 2.0;
+
+    writeln!(
+        writer,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{s}" height="{s}">"#,
+        s = size
+    )?;
+    // residues are placed clockwise from the top
+    let position = |theta: f32, r: f32| {
+        let t = theta.to_radians();
+        (centre + r * t.sin(), centre - r * t.cos())
+    };
+    for i in 1..seq.len() {
+        let (x1, y1) = position(((i - 1) as f32 * angle) % 360.0, ring((i - 1) / spokes));
+        let (x2, y2) = position((i as f32 * angle) % 360.0, ring(i / spokes));
+        writeln!(
+            writer,
+            r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="lightgrey"/>"#,
+            x1, y1, x2, y2
+        )?;
+    }
+    for (i, &aa) in seq.iter().enumerate() {
+        let (x, y) = position((i as f32 * angle) % 360.0, ring(i / spokes));
+        let fill = match scale.get(&aa) {
+            Some(&h) if h > 0.0 => "orange",
+            Some(_) => "lightblue",
+            None => "white",
+        };
+        writeln!(
+            writer,
+            r#"<circle cx="{:.1}" cy="{:.1}" r="{}" fill="{}" stroke="black"/>"#,
+            x, y, RADIUS, fill
+        )?;
+        writeln!(
+            writer,
+            r#"<text x="{:.1}" y="{:.1}" font-size="11" text-anchor="middle">{}{}</text>"#,
+            x,
+            y + 4.0,
+            aa as char,
+            i + 1
+        )?;
+    }
+    let (mx, my) = moment_vector(seq, scale, angle);
+    if mx != 0.0 || my != 0.0 {
+        let direction = my.atan2(mx).to_degrees();
+        let (x, y) = position(direction, ring(0) - RADIUS - 8.0);
+        writeln!(
+            writer,
+            r#"<line x1="{c}" y1="{c}" x2="{:.1}" y2="{:.1}" stroke="red" stroke-width="2"/>"#,
+            x,
+            y,
+            c = centre
+        )?;
+    }
+    writeln!(writer, "</svg>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seq_analysis::protein::params::hydropathy::EISENBERG;
+
+    #[test]
+    fn test_hydrophobic_moment() {
+        // at 180°, alternating residues all point the same way when their signs alternate
+        let mut scale = BTreeMap::new();
+        scale.insert(b'L', 1.0);
+        scale.insert(b'K', -1.0);
+        assert!((hydrophobic_moment(b"LKLKLK", &scale, 180.0) - 1.0).abs() < 1e-5);
+        assert!(hydrophobic_moment(b"LLLLLL", &scale, 180.0).abs() < 1e-5);
+        assert!((mean_hydrophobicity(b"LKLX", &scale) - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_amphipathic_segments() {
+        // an ideal amphipathic helix (hydrophobic every 3-4 residues) within a
+        // hydrophilic, non-periodic context
+        let seq = b"SGSGSGSGSGSGLKKLLKKLLKKLLKKLSGSGSGSGSGSG";
+        let segments = amphipathic_segments(seq, &EISENBERG, &AmphipathicParams::default());
+        // windows starting from 7 to 22 have a mean moment above 0.5
+        assert_eq!(segments, vec![7..33]);
+        let profile = moment_profile(seq, &EISENBERG, 11, ALPHA_HELIX);
+        assert_eq!(profile.values.len(), seq.len() - 10);
+    }
+
+    #[test]
+    fn test_helical_wheel() {
+        let wheel = helical_wheel(b"ACDEFGHIKLMNPQRSTVWY", ALPHA_HELIX);
+        assert_eq!(wheel.len(), 18);
+        assert_eq!(wheel[0], (0.0, vec![0, 18]));
+        assert_eq!(wheel[1], (20.0, vec![11]));
+        let mut text = Vec::new();
+        write_helical_wheel_text(b"ACDE", ALPHA_HELIX, &mut text).unwrap();
+        assert_eq!(
+            String::from_utf8(text).unwrap(),
+            "   0.0° A1\n 100.0° C2\n 200.0° D3\n 300.0° E4\n"
+        );
+        let mut svg = Vec::new();
+        write_helical_wheel_svg(b"LKKLLKKL", &EISENBERG, ALPHA_HELIX, &mut svg).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert_eq!(svg.matches("<circle").count(), 8);
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+}
@@ -0,0 +1,304 @@
+//! Isotopic distributions of molecules from their elemental formulae.
+//!
+//! The distribution of a molecule is the product of the polynomials
+//! `(p_1 x^{m_1} + p_2 x^{m_2} + ...)^n` of its elements, `p_i` and `m_i` being the
+//! natural abundances and masses of the isotopes of an element with `n` atoms. The
+//! powers are computed by repeated squaring, discarding the peaks below a threshold
+//! after each product so that the distributions stay short.
+//!
+//! - [`aggregated`](fn.aggregated.html) groups the isotopic variants by nominal mass
+//!   (i.e. by number of additional neutrons), each peak being at the mean mass of its
+//!   variants, as observed at low resolution;
+//! - [`fine`](fn.fine.html) keeps variants of the same nominal mass apart (e.g. the
+//!   ¹³C and ¹⁵N variants of a peptide), as observed at very high resolution.
+//!
+//! # Example
+//!
+//! ```
+//! use bioinformatics_algorithms::seq_analysis::protein::isotopes::aggregated;
+//! use bioinformatics_algorithms::seq_analysis::protein::ProteinSeqAnalysis;
+//! let formula = ProteinSeqAnalysis::new(b"PEPTIDE").calc_formula();
+//! let envelope = aggregated(&formula, 1e-6);
+//! assert!((envelope.peaks[0].mass - 799.35996).abs() < 1e-4);
+//! let mz = envelope.mz(2);
+//! assert!((mz[0].0 - 400.68726).abs() < 1e-4);
+//! // the monoisotopic peak is the most abundant for small peptides
+//! assert_eq!(envelope.most_abundant().unwrap().mass, envelope.peaks[0].mass);
+//! ```
+//!
+//! # References
+//!
+//! - [Rockwood A.L., Van Orden S.L., Smith R.D. (1995) Rapid calculation of isotope distributions. _Anal. Chem._ **67**: 2699-2704.](https://doi.org/10.1021/ac00111a031)
+//! - [de Laeter J.R. _et al_ (2003) Atomic weights of the elements: review 2000. _Pure Appl. Chem._ **75**: 683-800.](https://doi.org/10.1351/pac200375060683)
+
+use crate::seq_analysis::protein::mass::{Element, ElementalFormula, PROTON_MASS};
+
+/// Mass of the isotopes of an element, in Da, with their natural abundances
+pub fn isotopes(element: Element) -> &'static [(f64, f64)] {
+    match element {
+        Element::C => &[(12.0, 0.9893), (13.003_354_837_8, 0.0107)],
+        Element::H => &[(1.007_825_032_07, 0.999_885), (2.014_101_777_8, 0.000_115)],
+        Element::N => &[(14.003_074_004_8, 0.996_36), (15.000_108_898_2, 0.003_64)],
+        Element::O => &[
+            (15.994_914_619_56, 0.997_57),
+            (16.999_131_70, 0.000_38),
+            (17.999_161_0, 0.002_05),
+        ],
+        Element::P => &[(30.973_761_63, 1.0)],
+        Element::S => &[
+            (31.972_071_00, 0.9499),
+            (32.971_458_76, 0.0075),
+            (33.967_866_90, 0.0425),
+            (35.967_080_76, 0.0001),
+        ],
+        Element::Se => &[
+            (73.922_476_4, 0.0089),
+            (75.919_213_6, 0.0937),
+            (76.919_914_0, 0.0763),
+            (77.917_309_1, 0.2377),
+            (79.916_521_3, 0.4961),
+            (81.916_699_4, 0.0873),
+        ],
+    }
+}
+
+/// Peaks closer than this, in Da, are merged in fine distributions
+const FINE_RESOLUTION: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IsotopePeak {
+    /// Neutral mass, in Da
+    pub mass: f64,
+    /// Probability of the peak; the probabilities of a complete distribution sum to 1
+    pub abundance: f64,
+}
+
+/// Peaks of an isotopic distribution, by increasing mass
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IsotopeDistribution {
+    pub peaks: Vec<IsotopePeak>,
+}
+
+impl IsotopeDistribution {
+    /// The peak of highest abundance
+    pub fn most_abundant(&self) -> Option<&IsotopePeak> {
+        self.peaks
+            .iter()
+            .max_by(|a, b| a.abundance.partial_cmp(&b.abundance).unwrap())
+    }
+
+    /// Abundances relative to the most abundant peak, which is scaled to 100
+    pub fn relative_abundances(&self) -> Vec<f64> {
+        let max = self.most_abundant().map_or(1.0, |p| p.abundance);
+        self.peaks
+            .iter()
+            .map(|p| 100.0 * p.abundance / max)
+            .collect()
+    }
+
+    /// m/z and abundance of the peaks of the ion with `charge` protons added (or
+    /// removed, if negative)
+    pub fn mz(&self, charge: i32) -> Vec<(f64, f64)> {
+        assert!(charge != 0, "the charge must not be 0");
+        self.peaks
+            .iter()
+            .map(|p| {
+                (
+                    (p.mass + charge as f64 * PROTON_MASS) / charge.abs() as f64,
+                    p.abundance,
+                )
+            })
+            .collect()
+    }
+}
+
+/// Aggregated distribution, indexed by number of additional neutrons: the abundance of
+/// each peak with the abundance-weighted sum of its masses
+type Aggregated = Vec<(f64, f64)>;
+
+fn multiply_aggregated(a: &Aggregated, b: &Aggregated, threshold: f64) -> Aggregated {
+    let mut res = vec![(0.0, 0.0); a.len() + b.len() - 1];
+    for (i, &(pa, ma)) in a.iter().enumerate() {
+        for (j, &(pb, mb)) in b.iter().enumerate() {
+            // the mean mass of a product is the sum of the mean masses
+            res[i + j].0 += pa * pb;
+            res[i + j].1 += ma * pb + mb * pa;
+        }
+    }
+    while res.len() > 1 && res.last().unwrap().0 < threshold {
+        res.pop();
+    }
+    res
+}
+
+fn multiply_fine(a: &[IsotopePeak], b: &[IsotopePeak], threshold: f64) -> Vec<IsotopePeak> {
+    let mut products = Vec::with_capacity(a.len() * b.len());
+    for pa in a {
+        for pb in b {
+            let abundance = pa.abundance * pb.abundance;
+            if abundance >= threshold {
+                products.push(IsotopePeak {
+                    mass: pa.mass + pb.mass,
+                    abundance,
+                });
+            }
+        }
+    }
+    products.sort_by(|x, y| x.mass.partial_cmp(&y.mass).unwrap());
+    let mut res: Vec<IsotopePeak> = Vec::new();
+    for p in products {
+        match res.last_mut() {
+            Some(last) if p.mass - last.mass < FINE_RESOLUTION => {
+                let abundance = last.abundance + p.abundance;
+                last.mass = (last.mass * last.abundance + p.mass * p.abundance) / abundance;
+                last.abundance = abundance;
+            }
+            _ => res.push(p),
+        }
+    }
+    res
+}
+
+/// `base^n` by repeated squaring
+fn power<T: Clone>(base: T, mut n: u32, one: T, multiply: &dyn Fn(&T, &T) -> T) -> T {
+    let mut res = one;
+    let mut square = base;
+    while n > 0 {
+        if n % 2 == 1 {
+            res = multiply(&res, &square);
+        }
+        n /= 2;
+        if n > 0 {
+            square = multiply(&square, &square);
+        }
+    }
+    res
+}
+
+fn counts(formula: &ElementalFormula) -> impl Iterator<Item = (Element, u32)> + '_ {
+    formula.iter().map(|(element, n)| {
+        assert!(n >= 0, "negative count of {}", element.symbol());
+        (element, n as u32)
+    })
+}
+
+/// Isotopic distribution of `formula` aggregated by nominal mass. Peaks with an
+/// abundance below `threshold` are discarded from the high-mass end of the envelope.
+pub fn aggregated(formula: &ElementalFormula, threshold: f64) -> IsotopeDistribution {
+    let multiply = |a: &Aggregated, b: &Aggregated| multiply_aggregated(a, b, threshold);
+    let mut dist: Aggregated = vec![(1.0, 0.0)];
+    for (element, n) in counts(formula) {
+        let isotopes = isotopes(element);
+        let lightest = isotopes[0].0.round() as usize;
+        let mut base = vec![(0.0, 0.0); isotopes.last().unwrap().0.round() as usize - lightest + 1];
+        for &(mass, abundance) in isotopes {
+            base[mass.round() as usize - lightest] = (abundance, abundance * mass);
+        }
+        let element_dist = power(base, n, vec![(1.0, 0.0)], &multiply);
+        dist = multiply(&dist, &element_dist);
+    }
+    IsotopeDistribution {
+        peaks: dist
+            .into_iter()
+            .filter(|&(abundance, _)| abundance > 0.0)
+            .map(|(abundance, mass)| IsotopePeak {
+                mass: mass / abundance,
+                abundance,
+            })
+            .collect(),
+    }
+}
+
+/// Fine isotopic distribution of `formula`. Peaks with an abundance below `threshold`
+/// are discarded.
+pub fn fine(formula: &ElementalFormula, threshold: f64) -> IsotopeDistribution {
+    let multiply = |a: &Vec<IsotopePeak>, b: &Vec<IsotopePeak>| multiply_fine(a, b, threshold);
+    let one = vec![IsotopePeak {
+        mass: 0.0,
+        abundance: 1.0,
+    }];
+    let mut peaks = one.clone();
+    for (element, n) in counts(formula) {
+        let base = isotopes(element)
+            .iter()
+            .map(|&(mass, abundance)| IsotopePeak { mass, abundance })
+            .collect();
+        let element_dist = power(base, n, one.clone(), &multiply);
+        peaks = multiply(&peaks, &element_dist);
+    }
+    IsotopeDistribution { peaks }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seq_analysis::protein::mass::formula_from_counts;
+    use crate::seq_analysis::protein::ProteinSeqAnalysis;
+
+    #[test]
+    fn test_single_atoms() {
+        let carbon = ElementalFormula::from_counts(&[(Element::C, 1)]);
+        let dist = aggregated(&carbon, 0.0);
+        assert_eq!(dist.peaks.len(), 2);
+        assert!((dist.peaks[0].abundance - 0.9893).abs() < 1e-12);
+        assert!((dist.peaks[1].mass - 13.003_354_837_8).abs() < 1e-9);
+        let fine = fine(&carbon, 0.0);
+        assert_eq!(fine.peaks.len(), 2);
+        assert!((fine.peaks[1].mass - dist.peaks[1].mass).abs() < 1e-9);
+
+        // C2: 12C2, 12C13C, 13C2
+        let carbon2 = ElementalFormula::from_counts(&[(Element::C, 2)]);
+        let dist = aggregated(&carbon2, 0.0);
+        let expected = [0.9893 * 0.9893, 2.0 * 0.9893 * 0.0107, 0.0107 * 0.0107];
+        for (peak, p) in dist.peaks.iter().zip(expected.iter()) {
+            assert!((peak.abundance - p).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_fine_structure() {
+        // the M+1 peak of a peptide comprises 13C, 2H, 15N and 17O variants
+        let formula = ProteinSeqAnalysis::new(b"PEPTIDE").calc_formula();
+        let fine = fine(&formula, 1e-9);
+        let aggregated = aggregated(&formula, 1e-9);
+        let m1: Vec<&IsotopePeak> = fine
+            .peaks
+            .iter()
+            .filter(|p| (p.mass - aggregated.peaks[1].mass).abs() < 0.5)
+            .collect();
+        assert_eq!(m1.len(), 4);
+        // they add up to the aggregated peak, at its mean mass
+        let abundance: f64 = m1.iter().map(|p| p.abundance).sum();
+        let mass = m1.iter().map(|p| p.mass * p.abundance).sum::<f64>() / abundance;
+        assert!((abundance - aggregated.peaks[1].abundance).abs() < 1e-9);
+        assert!((mass - aggregated.peaks[1].mass).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_large_protein() {
+        // the mean of the envelope is the average mass
+        let mut count = std::collections::BTreeMap::new();
+        for &aa in b"ACDEFGHIKLMNPQRSTVWY" {
+            count.insert(aa, 50u32);
+        }
+        let formula = formula_from_counts(count.iter());
+        let dist = aggregated(&formula, 1e-12);
+        let total: f64 = dist.peaks.iter().map(|p| p.abundance).sum();
+        assert!((total - 1.0).abs() < 1e-6);
+        let mean = dist.peaks.iter().map(|p| p.mass * p.abundance).sum::<f64>();
+        let expected: f64 = formula
+            .iter()
+            .map(|(e, n)| n as f64 * isotopes(e).iter().map(|(m, p)| m * p).sum::<f64>())
+            .sum();
+        assert!((mean - expected).abs() < 1e-3);
+        // the monoisotopic peak is no longer the most abundant
+        assert!(dist.most_abundant().unwrap().mass > dist.peaks[0].mass + 5.0);
+        assert_eq!(
+            dist.relative_abundances()
+                .iter()
+                .cloned()
+                .fold(0.0, f64::max),
+            100.0
+        );
+    }
+}
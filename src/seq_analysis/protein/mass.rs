@@ -0,0 +1,216 @@
+//! Elemental composition and masses of amino acid residues.
+//!
+//! Masses are computed from elemental formulae. Monoisotopic masses use the mass of
+//! the most abundant isotope of each element; average masses use the atomic weights
+//! of ExPASy's Compute pI/Mw and ProtParam tools, so that average molecular weights
+//! agree with ProtParam.
+//!
+//! # References
+//!
+//! - [ExPASy: amino acid residue masses](https://web.expasy.org/findmod/findmod_masses.html)
+//! - [Wang M. _et al_ (2017) The AME2016 atomic mass evaluation. _Chinese Phys. C_ **41**: 030003.](https://doi.org/10.1088/1674-1137/41/3/030003)
+
+use lazy_static::lazy_static;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Mass of a proton, in Da
+pub const PROTON_MASS: f64 = 1.007_276_466_8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Element {
+    C,
+    H,
+    N,
+    O,
+    P,
+    S,
+    Se,
+}
+
+impl Element {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Element::C => "C",
+            Element::H => "H",
+            Element::N => "N",
+            Element::O => "O",
+            Element::P => "P",
+            Element::S => "S",
+            Element::Se => "Se",
+        }
+    }
+
+    /// Mass of the most abundant isotope, in Da
+    pub fn monoisotopic_mass(self) -> f64 {
+        match self {
+            Element::C => 12.0,
+            Element::H => 1.007_825_032_07,
+            Element::N => 14.003_074_004_8,
+            Element::O => 15.994_914_619_56,
+            Element::P => 30.973_761_63,
+            Element::S => 31.972_071_00,
+            Element::Se => 79.916_521_3,
+        }
+    }
+
+    /// Standard atomic weight, in Da
+    pub fn average_mass(self) -> f64 {
+        match self {
+            Element::C => 12.011,
+            Element::H => 1.007_94,
+            Element::N => 14.006_74,
+            Element::O => 15.9994,
+            Element::P => 30.973_762,
+            Element::S => 32.066,
+            Element::Se => 78.96,
+        }
+    }
+}
+
+/// Number of atoms of each element. Counts may be negative, e.g. for the loss of a
+/// water molecule.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ElementalFormula(BTreeMap<Element, i32>);
+
+impl ElementalFormula {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_counts(counts: &[(Element, i32)]) -> Self {
+        let mut res = Self::new();
+        for &(element, n) in counts {
+            res.add_element(element, n);
+        }
+        res
+    }
+
+    pub fn count(&self, element: Element) -> i32 {
+        self.0.get(&element).cloned().unwrap_or(0)
+    }
+
+    pub fn add_element(&mut self, element: Element, n: i32) {
+        let count = self.0.entry(element).or_insert(0);
+        *count += n;
+        if *count == 0 {
+            self.0.remove(&element);
+        }
+    }
+
+    /// Add `times` copies of `other` to this formula.
+    pub fn add(&mut self, other: &ElementalFormula, times: i32) {
+        for (&element, &n) in other.0.iter() {
+            self.add_element(element, n * times);
+        }
+    }
+
+    /// Iterate over the elements and their counts
+    pub fn iter(&self) -> impl Iterator<Item = (Element, i32)> + '_ {
+        self.0.iter().map(|(&e, &n)| (e, n))
+    }
+
+    pub fn total_atoms(&self) -> i32 {
+        self.0.values().sum()
+    }
+
+    pub fn monoisotopic_mass(&self) -> f64 {
+        self.iter()
+            .map(|(e, n)| e.monoisotopic_mass() * n as f64)
+            .sum()
+    }
+
+    pub fn average_mass(&self) -> f64 {
+        self.iter().map(|(e, n)| e.average_mass() * n as f64).sum()
+    }
+}
+
+impl fmt::Display for ElementalFormula {
+    /// Hill notation, e.g. `C3H7NO2`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (element, n) in self.iter() {
+            if n == 1 {
+                write!(f, "{}", element.symbol())?;
+            } else {
+                write!(f, "{}{}", element.symbol(), n)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+lazy_static! {
+    pub static ref WATER: ElementalFormula =
+        ElementalFormula::from_counts(&[(Element::H, 2), (Element::O, 1)]);
+    /// Formulae of amino acid residues, i.e. amino acids minus one water molecule
+    pub static ref RESIDUE_FORMULAS: BTreeMap<u8, ElementalFormula> = {
+        use Element::*;
+        let mut m = BTreeMap::new();
+        let mut insert = |aa: u8, c, h, n, o, s| {
+            m.insert(aa, ElementalFormula::from_counts(&[(C, c), (H, h), (N, n), (O, o), (S, s)]));
+        };
+        insert(b'A', 3, 5, 1, 1, 0);
+        insert(b'R', 6, 12, 4, 1, 0);
+        insert(b'N', 4, 6, 2, 2, 0);
+        insert(b'D', 4, 5, 1, 3, 0);
+        insert(b'C', 3, 5, 1, 1, 1);
+        insert(b'E', 5, 7, 1, 3, 0);
+        insert(b'Q', 5, 8, 2, 2, 0);
+        insert(b'G', 2, 3, 1, 1, 0);
+        insert(b'H', 6, 7, 3, 1, 0);
+        insert(b'I', 6, 11, 1, 1, 0);
+        insert(b'L', 6, 11, 1, 1, 0);
+        insert(b'K', 6, 12, 2, 1, 0);
+        insert(b'M', 5, 9, 1, 1, 1);
+        insert(b'F', 9, 9, 1, 1, 0);
+        insert(b'P', 5, 7, 1, 1, 0);
+        insert(b'S', 3, 5, 1, 2, 0);
+        insert(b'T', 4, 7, 1, 2, 0);
+        insert(b'W', 11, 10, 2, 1, 0);
+        insert(b'Y', 9, 9, 1, 2, 0);
+        insert(b'V', 5, 9, 1, 1, 0);
+        // pyrrolysine
+        insert(b'O', 12, 19, 3, 2, 0);
+        // selenocysteine
+        m.insert(b'U', ElementalFormula::from_counts(&[(C, 3), (H, 5), (N, 1), (O, 1), (Se, 1)]));
+        m
+    };
+}
+
+/// Elemental formula of a peptide or protein with the given residue counts,
+/// including the water of the free termini. Residues without a known formula are
+/// skipped.
+pub fn formula_from_counts<'a, I: IntoIterator<Item = (&'a u8, &'a u32)>>(
+    aa_count: I,
+) -> ElementalFormula {
+    let mut formula = WATER.clone();
+    for (aa, &n) in aa_count {
+        if let Some(residue) = RESIDUE_FORMULAS.get(aa) {
+            formula.add(residue, n as i32);
+        }
+    }
+    formula
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formula() {
+        let mut count = BTreeMap::new();
+        count.insert(b'G', 2u32);
+        let formula = formula_from_counts(count.iter());
+        assert_eq!(formula.to_string(), "C4H8N2O3");
+        assert_eq!(formula.total_atoms(), 17);
+    }
+
+    #[test]
+    fn test_masses() {
+        let glycine = formula_from_counts([(&b'G', &1u32)].iter().cloned());
+        assert!((glycine.monoisotopic_mass() - 75.03203).abs() < 1e-4);
+        assert!((glycine.average_mass() - 75.07).abs() < 0.01);
+    }
+}
@@ -0,0 +1,290 @@
+//! Peptide mass fingerprinting against a protein database.
+//!
+//! Each protein of the database is digested in silico, and the observed peptide
+//! masses are matched against the masses of its peptides. Proteins are ranked by their
+//! MOWSE score (Pappin _et al_, 1993): the peptides of the database are counted in a
+//! matrix of 100 Da peptide mass bins by 10 kDa protein mass bins, each column of
+//! which is normalised by its maximum to give frequencies `f`. A protein of mass `M`
+//! matching peptides of frequencies `f_1, ..., f_k` scores `50000 / (M Π f_i)`; as
+//! this grows very quickly with the number of matches, its decimal logarithm is
+//! reported.
+//!
+//! Observed masses are neutral masses: subtract the mass of a proton
+//! ([`PROTON_MASS`](../mass/constant.PROTON_MASS.html)) from `[M+H]+` values.
+//! Peptides containing residues without a known mass are left out.
+//!
+//! # Example
+//!
+//! ```
+//! use bioinformatics_algorithms::seq_analysis::protein::mass_fingerprint::*;
+//! let db = b">P1 first protein\nMAGKLLSTRPEPTIDEKWWAGR\n>P2 second protein\nMCHHKAACDRGGGYLKST\n";
+//! // masses of AACDR and GGGYLK, within 10 ppm
+//! let observed = [534.2220, 593.3173];
+//! let hits = search(&db[..], &observed, &PmfParams::default()).unwrap();
+//! assert_eq!(hits[0].id, "P2");
+//! assert_eq!(hits[0].matches.len(), 2);
+//! assert!((hits[0].coverage - 11.0 / 18.0).abs() < 1e-9);
+//! ```
+//!
+//! # References
+//!
+//! - [Pappin D.J.C., Hojrup P., Bleasby A.J. (1993) Rapid identification of proteins by peptide-mass fingerprinting. _Curr. Biol._ **3**: 327-332.](https://doi.org/10.1016/0960-9822(93)90195-T)
+
+use crate::seq_analysis::masking::merge_intervals;
+use crate::seq_analysis::protein::digestion::{self, DigestParams, Protease};
+use crate::seq_analysis::protein::mass::RESIDUE_FORMULAS;
+use bio::io::fasta;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead};
+use std::ops::Range;
+
+/// Width of the peptide mass bins of the MOWSE matrix, in Da
+const PEPTIDE_BIN: f64 = 100.0;
+/// Width of the protein mass bins of the MOWSE matrix, in Da
+const PROTEIN_BIN: f64 = 10_000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tolerance {
+    /// Parts per million of the theoretical mass
+    Ppm(f64),
+    Da(f64),
+}
+
+impl Tolerance {
+    /// Whether `observed` is within the tolerance of `theoretical`
+    pub fn matches(self, observed: f64, theoretical: f64) -> bool {
+        let delta = (observed - theoretical).abs();
+        match self {
+            Tolerance::Ppm(ppm) => delta <= theoretical * ppm * 1e-6,
+            Tolerance::Da(da) => delta <= da,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MassType {
+    Monoisotopic,
+    Average,
+}
+
+#[derive(Debug, Clone)]
+pub struct PmfParams {
+    pub protease: Protease,
+    pub digest: DigestParams,
+    pub tolerance: Tolerance,
+    pub mass_type: MassType,
+    /// Minimum number of matched masses of a reported protein
+    pub min_matches: usize,
+}
+
+impl Default for PmfParams {
+    /// Trypsin with up to one missed cleavage, monoisotopic masses within 10 ppm, and
+    /// proteins matching at least one mass
+    fn default() -> Self {
+        PmfParams {
+            protease: Protease::trypsin(),
+            digest: DigestParams {
+                missed_cleavages: 1,
+                ..Default::default()
+            },
+            tolerance: Tolerance::Ppm(10.0),
+            mass_type: MassType::Monoisotopic,
+            min_matches: 1,
+        }
+    }
+}
+
+/// An observed mass matched to a peptide
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeptideMatch {
+    pub observed: f64,
+    pub theoretical: f64,
+    /// Position of the peptide in the protein, 0-based and half-open
+    pub range: Range<usize>,
+    pub missed_cleavages: usize,
+}
+
+impl PeptideMatch {
+    /// Mass error, in parts per million
+    pub fn error_ppm(&self) -> f64 {
+        (self.observed - self.theoretical) / self.theoretical * 1e6
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PmfHit {
+    pub id: String,
+    pub description: Option<String>,
+    /// Mass of the protein, in Da
+    pub mass: f64,
+    /// Matches of the observed masses, each mass matching its closest peptide
+    pub matches: Vec<PeptideMatch>,
+    /// Decimal logarithm of the MOWSE score
+    pub score: f64,
+    /// Fraction of the residues covered by the matched peptides
+    pub coverage: f64,
+}
+
+/// A digested protein: its mass and its peptides as (range, missed cleavages, mass)
+struct Digest {
+    mass: f64,
+    peptides: Vec<(Range<usize>, usize, f64)>,
+}
+
+fn digest_protein(seq: &[u8], params: &PmfParams) -> Digest {
+    let whole = digestion::Peptide::new(seq, 0, seq.len(), 0);
+    let mass = |p: &digestion::Peptide<'_>| match params.mass_type {
+        MassType::Monoisotopic => p.monoisotopic_mass,
+        MassType::Average => p.average_mass,
+    };
+    Digest {
+        mass: mass(&whole),
+        peptides: digestion::digest(seq, &params.protease, &params.digest)
+            .iter()
+            .filter(|p| p.seq.iter().all(|aa| RESIDUE_FORMULAS.contains_key(aa)))
+            .map(|p| (p.start..p.end, p.missed_cleavages, mass(p)))
+            .collect(),
+    }
+}
+
+fn protein_bin(mass: f64) -> usize {
+    (mass / PROTEIN_BIN) as usize
+}
+
+fn peptide_bin(mass: f64) -> usize {
+    (mass / PEPTIDE_BIN) as usize
+}
+
+/// MOWSE frequencies, indexed by protein and peptide mass bins
+fn mowse_matrix(digests: &[Digest]) -> BTreeMap<(usize, usize), f64> {
+    let mut counts: BTreeMap<(usize, usize), f64> = BTreeMap::new();
+    for digest in digests {
+        let j = protein_bin(digest.mass);
+        for (_, _, mass) in &digest.peptides {
+            *counts.entry((j, peptide_bin(*mass))).or_insert(0.0) += 1.0;
+        }
+    }
+    let mut max: BTreeMap<usize, f64> = BTreeMap::new();
+    for (&(j, _), &n) in &counts {
+        let m = max.entry(j).or_insert(0.0);
+        *m = m.max(n);
+    }
+    for ((j, _), n) in counts.iter_mut() {
+        *n /= max[j];
+    }
+    counts
+}
+
+/// Search the observed masses against `records`, returning the proteins with at least
+/// `params.min_matches` matched masses by decreasing score
+pub fn search_records(
+    records: &[fasta::Record],
+    observed: &[f64],
+    params: &PmfParams,
+) -> Vec<PmfHit> {
+    let digests: Vec<Digest> = records
+        .iter()
+        .map(|r| digest_protein(r.seq(), params))
+        .collect();
+    let mowse = mowse_matrix(&digests);
+    let mut hits = Vec::new();
+    for (record, digest) in records.iter().zip(&digests) {
+        let mut matches = Vec::new();
+        for &mass in observed {
+            let closest = digest
+                .peptides
+                .iter()
+                .filter(|(_, _, theoretical)| params.tolerance.matches(mass, *theoretical))
+                .min_by(|a, b| (a.2 - mass).abs().partial_cmp(&(b.2 - mass).abs()).unwrap());
+            if let Some((range, missed_cleavages, theoretical)) = closest {
+                matches.push(PeptideMatch {
+                    observed: mass,
+                    theoretical: *theoretical,
+                    range: range.clone(),
+                    missed_cleavages: *missed_cleavages,
+                });
+            }
+        }
+        if matches.is_empty() || matches.len() < params.min_matches {
+            continue;
+        }
+        let j = protein_bin(digest.mass);
+        let score = 50_000f64.log10()
+            - digest.mass.log10()
+            - matches
+                .iter()
+                .map(|m| mowse[&(j, peptide_bin(m.theoretical))].log10())
+                .sum::<f64>();
+        let covered: usize = merge_intervals(matches.iter().map(|m| m.range.clone()).collect())
+            .iter()
+            .map(|r| r.len())
+            .sum();
+        hits.push(PmfHit {
+            id: record.id().to_owned(),
+            description: record.desc().map(|d| d.to_owned()),
+            mass: digest.mass,
+            coverage: covered as f64 / record.seq().len() as f64,
+            matches,
+            score,
+        });
+    }
+    hits.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+    hits
+}
+
+/// Search the observed masses against the proteins of a FASTA database
+pub fn search<R: BufRead>(
+    reader: R,
+    observed: &[f64],
+    params: &PmfParams,
+) -> io::Result<Vec<PmfHit>> {
+    let records = fasta::Reader::new(reader)
+        .records()
+        .collect::<io::Result<Vec<_>>>()?;
+    Ok(search_records(&records, observed, params))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tolerance() {
+        assert!(Tolerance::Ppm(10.0).matches(1000.01, 1000.0));
+        assert!(!Tolerance::Ppm(10.0).matches(1000.011, 1000.0));
+        assert!(Tolerance::Da(0.5).matches(999.6, 1000.0));
+        assert!(!Tolerance::Da(0.5).matches(1000.6, 1000.0));
+    }
+
+    #[test]
+    fn test_search() {
+        let db = b">A\nMKAAAKGGGKLLLR\n>B\nMKAAAKWWWRLLLR\n>C\nMPPPPPPPP\n";
+        let mass = |seq: &[u8]| digestion::Peptide::new(seq, 0, seq.len(), 0).monoisotopic_mass;
+        // AAAK is shared, WWWR and LLLR are specific to B, with an error of 2 ppm
+        let observed: Vec<f64> = [&b"AAAK"[..], b"WWWR", b"LLLR"]
+            .iter()
+            .map(|p| mass(p) * (1.0 + 2e-6))
+            .chain(std::iter::once(1234.5))
+            .collect();
+        let params = PmfParams {
+            digest: DigestParams::default(),
+            ..Default::default()
+        };
+        let hits = search(&db[..], &observed, &params).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].id, "B");
+        assert_eq!(hits[0].matches.len(), 3);
+        assert!((hits[0].matches[0].error_ppm() - 2.0).abs() < 1e-3);
+        assert_eq!(hits[0].matches[1].range, 6..10);
+        assert!((hits[0].coverage - 12.0 / 14.0).abs() < 1e-9);
+        assert_eq!(hits[1].id, "A");
+        assert!(hits[0].score > hits[1].score);
+
+        let params = PmfParams {
+            min_matches: 3,
+            tolerance: Tolerance::Ppm(1.0),
+            ..params
+        };
+        assert!(search(&db[..], &observed, &params).unwrap().is_empty());
+    }
+}
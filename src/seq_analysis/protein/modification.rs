@@ -0,0 +1,598 @@
+//! Post-translational modifications and modified peptide sequences.
+//!
+//! Modified sequences are written in a subset of the ProForma notation: each
+//! modification follows the residue it modifies in square brackets, N-terminal
+//! modifications precede the sequence and C-terminal modifications follow it, separated
+//! by a hyphen. A modification is given by its Unimod name (case-insensitive), its
+//! Unimod accession or a mass delta in Da:
+//!
+//! ```text
+//! [Acetyl]-PEPT[Phospho]IDEM[UNIMOD:35]K[+14.0157]-[Amidated]
+//! ```
+//!
+//! Modifications can also be applied from a [`ModificationSet`](struct.ModificationSet.html)
+//! of fixed and variable modifications, as in database searches.
+//!
+//! # Example
+//!
+//! ```
+//! use bioinformatics_algorithms::seq_analysis::protein::modification::ModifiedPeptide;
+//! let peptide: ModifiedPeptide = "PEPT[Phospho]IDE".parse().unwrap();
+//! assert_eq!(peptide.seq, b"PEPTIDE");
+//! assert!((peptide.monoisotopic_mass() - 879.3263).abs() < 1e-3);
+//! assert_eq!(peptide.to_string(), "PEPT[Phospho]IDE");
+//! ```
+//!
+//! # References
+//!
+//! - [Unimod](https://www.unimod.org)
+//! - [LeDuc R.D. _et al_ (2022) Proteomics Standards Initiative's ProForma 2.0: unifying the encoding of proteoforms and peptidoforms. _J. Proteome Res._ **21**: 1189-1195.](https://doi.org/10.1021/acs.jproteome.1c00771)
+
+use crate::seq_analysis::protein::alphabet::InvalidResidue;
+use crate::seq_analysis::protein::digestion::{self, DigestParams, Peptide, Protease};
+use crate::seq_analysis::protein::fragmentation::{
+    self, FragmentIon, FragmentParams, ModificationMasses,
+};
+use crate::seq_analysis::protein::mass::{self, ElementalFormula};
+use crate::seq_analysis::protein::ProteinSeqAnalysis;
+use lazy_static::lazy_static;
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+/// A modification, with the mass it adds to the unmodified residue
+#[derive(Debug, Clone, PartialEq)]
+pub struct Modification {
+    pub name: String,
+    /// Unimod accession number
+    pub accession: Option<u32>,
+    pub monoisotopic_mass: f64,
+    pub average_mass: f64,
+}
+
+impl Modification {
+    /// A modification known only by its mass delta, named after it (e.g. `+14.0157`)
+    pub fn from_mass(delta: f64) -> Self {
+        Modification {
+            name: format!("{:+}", delta),
+            accession: None,
+            monoisotopic_mass: delta,
+            average_mass: delta,
+        }
+    }
+
+    /// Look up a modification of [`UNIMOD`](struct.UNIMOD.html) by name
+    /// (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        UNIMOD
+            .iter()
+            .find(|m| m.name.eq_ignore_ascii_case(name))
+            .cloned()
+    }
+
+    /// Look up a modification of [`UNIMOD`](struct.UNIMOD.html) by accession number
+    pub fn from_accession(accession: u32) -> Option<Self> {
+        UNIMOD
+            .iter()
+            .find(|m| m.accession == Some(accession))
+            .cloned()
+    }
+}
+
+impl fmt::Display for Modification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+lazy_static! {
+    /// Common modifications, with their Unimod names, accessions and mass deltas
+    pub static ref UNIMOD: Vec<Modification> = {
+        let m = |name: &str, accession, monoisotopic_mass, average_mass| Modification {
+            name: name.to_owned(),
+            accession: Some(accession),
+            monoisotopic_mass,
+            average_mass,
+        };
+        vec![
+            m("Acetyl", 1, 42.010565, 42.0367),
+            m("Amidated", 2, -0.984016, -0.9848),
+            m("Biotin", 3, 226.077598, 226.2954),
+            m("Carbamidomethyl", 4, 57.021464, 57.0513),
+            m("Carbamyl", 5, 43.005814, 43.0247),
+            m("Deamidated", 7, 0.984016, 0.9848),
+            m("Phospho", 21, 79.966331, 79.9799),
+            m("Propionamide", 24, 71.037114, 71.0779),
+            m("Glu->pyro-Glu", 27, -18.010565, -18.0153),
+            m("Gln->pyro-Glu", 28, -17.026549, -17.0305),
+            m("Methyl", 34, 14.01565, 14.0266),
+            m("Oxidation", 35, 15.994915, 15.9994),
+            m("Dimethyl", 36, 28.0313, 28.0532),
+            m("Trimethyl", 37, 42.04695, 42.0797),
+            m("Sulfo", 40, 79.956815, 80.0632),
+            m("Hex", 41, 162.052824, 162.1406),
+            m("HexNAc", 43, 203.079373, 203.1925),
+            m("Myristoyl", 45, 210.198366, 210.3556),
+            m("Palmitoyl", 47, 238.229666, 238.4088),
+            m("GlyGly", 121, 114.042927, 114.1026),
+            m("Formyl", 122, 27.994915, 28.0101),
+            m("Nitro", 354, 44.985078, 44.9976),
+            m("TMT6plex", 737, 229.162932, 229.2634),
+        ]
+    };
+}
+
+/// Error in a modified sequence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 0-based position of the error in the input
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl Error for ParseError {}
+
+/// A peptide or protein sequence with its modifications
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModifiedPeptide {
+    pub seq: Vec<u8>,
+    /// Modifications of each residue
+    pub residue_mods: Vec<Vec<Modification>>,
+    pub n_term: Vec<Modification>,
+    pub c_term: Vec<Modification>,
+}
+
+impl ModifiedPeptide {
+    /// An unmodified sequence
+    pub fn new(seq: &[u8]) -> Self {
+        ModifiedPeptide {
+            seq: seq.to_vec(),
+            residue_mods: vec![Vec::new(); seq.len()],
+            n_term: Vec::new(),
+            c_term: Vec::new(),
+        }
+    }
+
+    /// All modifications, N-terminal first
+    pub fn modifications(&self) -> impl Iterator<Item = &Modification> {
+        self.n_term
+            .iter()
+            .chain(self.residue_mods.iter().flatten())
+            .chain(self.c_term.iter())
+    }
+
+    pub fn is_modified(&self) -> bool {
+        self.modifications().next().is_some()
+    }
+
+    /// Elemental formula of the unmodified sequence. Ambiguous residues are left out.
+    pub fn unmodified_formula(&self) -> ElementalFormula {
+        mass::formula_from_counts(ProteinSeqAnalysis::count_aa(&self.seq).iter())
+    }
+
+    /// Monoisotopic mass, in Da, counting ambiguous residues as the mean of the
+    /// residues they stand for
+    pub fn monoisotopic_mass(&self) -> f64 {
+        ProteinSeqAnalysis::new(&self.seq).calc_monoisotopic_mass()
+            + self
+                .modifications()
+                .map(|m| m.monoisotopic_mass)
+                .sum::<f64>()
+    }
+
+    /// Average mass, in Da, counting ambiguous residues as the mean of the residues
+    /// they stand for
+    pub fn average_mass(&self) -> f64 {
+        ProteinSeqAnalysis::new(&self.seq).calc_molecular_weight()
+            + self.modifications().map(|m| m.average_mass).sum::<f64>()
+    }
+
+    /// Monoisotopic mass deltas of the modifications, as used for fragment ions
+    pub fn modification_masses(&self) -> ModificationMasses {
+        let sum = |mods: &[Modification]| mods.iter().map(|m| m.monoisotopic_mass).sum();
+        ModificationMasses {
+            variable: self
+                .residue_mods
+                .iter()
+                .enumerate()
+                .filter(|(_, mods)| !mods.is_empty())
+                .map(|(i, mods)| (i, sum(mods)))
+                .collect(),
+            n_term: sum(&self.n_term),
+            c_term: sum(&self.c_term),
+            ..Default::default()
+        }
+    }
+
+    /// Fragment ions of the peptide, or an error if it contains a residue without a
+    /// known mass, such as an ambiguity code
+    pub fn fragment_ions(
+        &self,
+        params: &FragmentParams,
+    ) -> Result<Vec<FragmentIon>, InvalidResidue> {
+        fragmentation::fragment_ions(&self.seq, &self.modification_masses(), params)
+    }
+
+    /// The residues in `range`, with their modifications. Terminal modifications are
+    /// kept if the range includes the terminus.
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        ModifiedPeptide {
+            seq: self.seq[range.clone()].to_vec(),
+            residue_mods: self.residue_mods[range.clone()].to_vec(),
+            n_term: if range.start == 0 {
+                self.n_term.clone()
+            } else {
+                Vec::new()
+            },
+            c_term: if range.end == self.seq.len() {
+                self.c_term.clone()
+            } else {
+                Vec::new()
+            },
+        }
+    }
+
+    /// Digest the sequence with `protease`. Each peptide keeps the modifications of its
+    /// residues, which are included in the masses of the
+    /// [`Peptide`](../digestion/struct.Peptide.html)s.
+    pub fn digest(
+        &self,
+        protease: &Protease,
+        params: &DigestParams,
+    ) -> Vec<(Peptide<'_>, ModifiedPeptide)> {
+        digestion::digest(&self.seq, protease, params)
+            .into_iter()
+            .map(|mut peptide| {
+                let modified = self.slice(peptide.start..peptide.end);
+                peptide.monoisotopic_mass = modified.monoisotopic_mass();
+                peptide.average_mass = modified.average_mass();
+                (peptide, modified)
+            })
+            .collect()
+    }
+}
+
+fn parse_modification(s: &str, position: usize) -> Result<Modification, ParseError> {
+    let error = |message: String| ParseError { position, message };
+    if s.starts_with('+') || s.starts_with('-') {
+        return s
+            .parse()
+            .map(Modification::from_mass)
+            .map_err(|_| error(format!("invalid mass delta '{}'", s)));
+    }
+    if s.len() > 7 && s.as_bytes()[..7].eq_ignore_ascii_case(b"UNIMOD:") {
+        let accession = s[7..]
+            .parse()
+            .map_err(|_| error(format!("invalid Unimod accession '{}'", s)))?;
+        return Modification::from_accession(accession)
+            .ok_or_else(|| error(format!("unknown Unimod accession '{}'", s)));
+    }
+    Modification::from_name(s).ok_or_else(|| error(format!("unknown modification '{}'", s)))
+}
+
+/// Parse the modifications in brackets starting at `*i`, advancing `*i` past them
+fn parse_brackets(s: &str, i: &mut usize) -> Result<Vec<Modification>, ParseError> {
+    let mut mods = Vec::new();
+    while s[*i..].starts_with('[') {
+        let end = s[*i..].find(']').ok_or_else(|| ParseError {
+            position: *i,
+            message: "unclosed '['".to_owned(),
+        })? + *i;
+        mods.push(parse_modification(&s[*i + 1..end], *i + 1)?);
+        *i = end + 1;
+    }
+    Ok(mods)
+}
+
+impl FromStr for ModifiedPeptide {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        let mut peptide = ModifiedPeptide::default();
+        let mut i = 0;
+        if s.starts_with('[') {
+            peptide.n_term = parse_brackets(s, &mut i)?;
+            if !s[i..].starts_with('-') {
+                return Err(ParseError {
+                    position: i,
+                    message: "expected '-' after the N-terminal modifications".to_owned(),
+                });
+            }
+            i += 1;
+        }
+        while i < s.len() {
+            let c = s.as_bytes()[i];
+            if c == b'-' {
+                i += 1;
+                peptide.c_term = parse_brackets(s, &mut i)?;
+                if peptide.c_term.is_empty() || i < s.len() {
+                    return Err(ParseError {
+                        position: i,
+                        message: "expected C-terminal modifications after '-'".to_owned(),
+                    });
+                }
+                break;
+            }
+            if !c.is_ascii_uppercase() {
+                return Err(ParseError {
+                    position: i,
+                    message: format!("unexpected character '{}'", s[i..].chars().next().unwrap()),
+                });
+            }
+            i += 1;
+            peptide.seq.push(c);
+            let mods = parse_brackets(s, &mut i)?;
+            peptide.residue_mods.push(mods);
+        }
+        if peptide.seq.is_empty() {
+            return Err(ParseError {
+                position: i,
+                message: "empty sequence".to_owned(),
+            });
+        }
+        Ok(peptide)
+    }
+}
+
+impl fmt::Display for ModifiedPeptide {
+    /// ProForma notation, e.g. `[Acetyl]-PEPT[Phospho]IDE`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for m in &self.n_term {
+            write!(f, "[{}]", m)?;
+        }
+        if !self.n_term.is_empty() {
+            write!(f, "-")?;
+        }
+        for (&aa, mods) in self.seq.iter().zip(&self.residue_mods) {
+            write!(f, "{}", aa as char)?;
+            for m in mods {
+                write!(f, "[{}]", m)?;
+            }
+        }
+        if !self.c_term.is_empty() {
+            write!(f, "-")?;
+        }
+        for m in &self.c_term {
+            write!(f, "[{}]", m)?;
+        }
+        Ok(())
+    }
+}
+
+/// Where a modification of a [`ModificationSet`](struct.ModificationSet.html) applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Site {
+    Residue(u8),
+    NTerm,
+    CTerm,
+}
+
+/// Fixed and variable modifications, as in the settings of a database search
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModificationSet {
+    /// Modifications applied to every matching site
+    pub fixed: Vec<(Modification, Site)>,
+    /// Modifications which may or may not be present on each matching site
+    pub variable: Vec<(Modification, Site)>,
+    /// Maximum number of variable modifications per peptide
+    pub max_variable: usize,
+}
+
+impl Default for ModificationSet {
+    /// No modifications, and at most 3 variable modifications per peptide
+    fn default() -> Self {
+        ModificationSet {
+            fixed: Vec::new(),
+            variable: Vec::new(),
+            max_variable: 3,
+        }
+    }
+}
+
+impl ModificationSet {
+    fn sites(site: Site, seq: &[u8]) -> Vec<Option<usize>> {
+        match site {
+            Site::Residue(aa) => (0..seq.len()).filter(|&i| seq[i] == aa).map(Some).collect(),
+            // terminal sites are encoded as None (N-term) and Some(len) (C-term)
+            Site::NTerm => vec![None],
+            Site::CTerm => vec![Some(seq.len())],
+        }
+    }
+
+    fn add(peptide: &mut ModifiedPeptide, site: Option<usize>, m: &Modification) {
+        match site {
+            None => peptide.n_term.push(m.clone()),
+            Some(i) if i == peptide.seq.len() => peptide.c_term.push(m.clone()),
+            Some(i) => peptide.residue_mods[i].push(m.clone()),
+        }
+    }
+
+    /// All modified forms of `seq`: the fixed modifications are applied to every
+    /// matching site, and each combination of at most `max_variable` variable
+    /// modifications on distinct sites gives a form, starting with the form without
+    /// variable modifications.
+    pub fn apply(&self, seq: &[u8]) -> Vec<ModifiedPeptide> {
+        let mut base = ModifiedPeptide::new(seq);
+        for (m, site) in &self.fixed {
+            for s in Self::sites(*site, seq) {
+                Self::add(&mut base, s, m);
+            }
+        }
+        let candidates: Vec<(Option<usize>, &Modification)> = self
+            .variable
+            .iter()
+            .flat_map(|(m, site)| Self::sites(*site, seq).into_iter().map(move |s| (s, m)))
+            .collect();
+        let mut forms = Vec::new();
+        let mut chosen = Vec::new();
+        self.combine(&base, &candidates, 0, &mut chosen, &mut forms);
+        forms
+    }
+
+    fn combine(
+        &self,
+        base: &ModifiedPeptide,
+        candidates: &[(Option<usize>, &Modification)],
+        from: usize,
+        chosen: &mut Vec<usize>,
+        forms: &mut Vec<ModifiedPeptide>,
+    ) {
+        let mut form = base.clone();
+        for &c in chosen.iter() {
+            Self::add(&mut form, candidates[c].0, candidates[c].1);
+        }
+        forms.push(form);
+        if chosen.len() == self.max_variable {
+            return;
+        }
+        for c in from..candidates.len() {
+            if chosen.iter().any(|&o| candidates[o].0 == candidates[c].0) {
+                continue;
+            }
+            chosen.push(c);
+            self.combine(base, candidates, c + 1, chosen, forms);
+            chosen.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seq_analysis::protein::fragmentation::IonType;
+
+    #[test]
+    fn test_parse() {
+        let peptide: ModifiedPeptide =
+            "[Acetyl]-PEPT[Phospho]IDEM[UNIMOD:35]K[+14.0157]-[Amidated]"
+                .parse()
+                .unwrap();
+        assert_eq!(peptide.seq, b"PEPTIDEMK");
+        assert_eq!(peptide.n_term[0].accession, Some(1));
+        assert_eq!(peptide.residue_mods[3][0].name, "Phospho");
+        assert_eq!(peptide.residue_mods[7][0].name, "Oxidation");
+        assert_eq!(peptide.residue_mods[8][0].monoisotopic_mass, 14.0157);
+        assert_eq!(peptide.c_term[0].name, "Amidated");
+        assert_eq!(
+            peptide.to_string(),
+            "[Acetyl]-PEPT[Phospho]IDEM[Oxidation]K[+14.0157]-[Amidated]"
+        );
+        assert_eq!(
+            "pept[phospho]"
+                .parse::<ModifiedPeptide>()
+                .unwrap_err()
+                .position,
+            0
+        );
+        assert_eq!(
+            "PEPT[Foo]".parse::<ModifiedPeptide>().unwrap_err().position,
+            5
+        );
+        assert_eq!(
+            "PEPT[Phospho"
+                .parse::<ModifiedPeptide>()
+                .unwrap_err()
+                .position,
+            4
+        );
+        assert!("[Acetyl]PEPTIDE".parse::<ModifiedPeptide>().is_err());
+        assert!("PEPTIDE-".parse::<ModifiedPeptide>().is_err());
+        // non-ASCII characters are reported, not sliced through
+        assert_eq!(
+            "PEPT[Phosphé]"
+                .parse::<ModifiedPeptide>()
+                .unwrap_err()
+                .position,
+            5
+        );
+        assert_eq!("PEPÉ".parse::<ModifiedPeptide>().unwrap_err().position, 3);
+    }
+
+    #[test]
+    fn test_masses() {
+        let plain = ModifiedPeptide::new(b"PEPTIDE");
+        assert!((plain.monoisotopic_mass() - 799.35996).abs() < 1e-4);
+        let modified: ModifiedPeptide = "[Acetyl]-PEPTIDE".parse().unwrap();
+        assert!(
+            (modified.monoisotopic_mass() - plain.monoisotopic_mass() - 42.010565).abs() < 1e-9
+        );
+        assert!((modified.average_mass() - plain.average_mass() - 42.0367).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fragments() {
+        let peptide: ModifiedPeptide = "PEPT[Phospho]IDE".parse().unwrap();
+        let params = FragmentParams {
+            ion_types: vec![IonType::B],
+            max_charge: 1,
+            neutral_losses: vec![],
+        };
+        let plain = ModifiedPeptide::new(b"PEPTIDE")
+            .fragment_ions(&params)
+            .unwrap();
+        let modified = peptide.fragment_ions(&params).unwrap();
+        assert_eq!(plain[2].mz, modified[2].mz);
+        assert!((modified[3].mz - plain[3].mz - 79.966331).abs() < 1e-9);
+        // ambiguity codes are accepted by the parser but have no fragment masses
+        let ambiguous: ModifiedPeptide = "PEPXIDE".parse().unwrap();
+        assert_eq!(ambiguous.fragment_ions(&params).unwrap_err().position, 3);
+        let expected = ProteinSeqAnalysis::new(b"PEPXIDE").calc_monoisotopic_mass();
+        assert_eq!(ambiguous.monoisotopic_mass(), expected);
+        // X weighs the mean residue mass, about 119 Da
+        let without = ModifiedPeptide::new(b"PEPIDE").monoisotopic_mass();
+        assert!((ambiguous.monoisotopic_mass() - without - 118.9).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_digest() {
+        let protein: ModifiedPeptide = "[Acetyl]-MAK[Methyl]LSRGS[Phospho]K".parse().unwrap();
+        let peptides = protein.digest(&Protease::trypsin(), &DigestParams::default());
+        let forms: Vec<String> = peptides.iter().map(|(_, p)| p.to_string()).collect();
+        assert_eq!(forms, vec!["[Acetyl]-MAK[Methyl]", "LSR", "GS[Phospho]K"]);
+        assert_eq!(peptides[2].0.start, 6);
+        for (peptide, modified) in &peptides {
+            assert_eq!(peptide.monoisotopic_mass, modified.monoisotopic_mass());
+            assert_eq!(peptide.average_mass, modified.average_mass());
+        }
+        // GSK with a phosphate
+        assert!((peptides[2].0.monoisotopic_mass - (290.1590 + 79.96633)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_modification_set() {
+        let set = ModificationSet {
+            fixed: vec![(
+                Modification::from_name("Carbamidomethyl").unwrap(),
+                Site::Residue(b'C'),
+            )],
+            variable: vec![
+                (
+                    Modification::from_name("Oxidation").unwrap(),
+                    Site::Residue(b'M'),
+                ),
+                (Modification::from_name("Acetyl").unwrap(), Site::NTerm),
+            ],
+            max_variable: 2,
+        };
+        let forms: Vec<String> = set.apply(b"MCMK").iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            forms,
+            vec![
+                "MC[Carbamidomethyl]MK",
+                "M[Oxidation]C[Carbamidomethyl]MK",
+                "M[Oxidation]C[Carbamidomethyl]M[Oxidation]K",
+                "[Acetyl]-M[Oxidation]C[Carbamidomethyl]MK",
+                "MC[Carbamidomethyl]M[Oxidation]K",
+                "[Acetyl]-MC[Carbamidomethyl]M[Oxidation]K",
+                "[Acetyl]-MC[Carbamidomethyl]MK",
+            ]
+        );
+    }
+}
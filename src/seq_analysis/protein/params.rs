@@ -1,43 +1,415 @@
-// use crate::seq_analysis::protein::AminoAcidCount;
-// use bio::utils::TextSlice;
 use lazy_static::lazy_static;
 use std::collections::BTreeMap;
 
+/// The 20 standard amino acids, in the order used by the matrices below
+pub const AMINO_ACIDS: &[u8; 20] = b"ACDEFGHIKLMNPQRSTVWY";
+
+/// Index of a standard amino acid in [AMINO_ACIDS](constant.AMINO_ACIDS.html)
+pub fn amino_acid_index(aa: u8) -> Option<usize> {
+    AMINO_ACIDS.iter().position(|&x| x == aa)
+}
+
+/// pKa scales for estimating the net charge and isoelectric point of proteins.
+///
+/// The values of the named scales are those compiled by Kozlowski (2016).
+///
+/// # References
+///
+/// - [Kozlowski L.P. (2016) IPC – Isoelectric Point Calculator. _Biol. Direct_ **11**: 55.](https://doi.org/10.1186/s13062-016-0159-9)
+/// - [Bjellqvist B. _et al_ (1993) The focusing positions of polypeptides in immobilized pH gradients can be predicted from their amino acid sequences. _Electrophoresis_ **14**: 1023-1031.](https://doi.org/10.1002/elps.11501401163)
 pub mod isoelectric_point {
     use super::*;
-    const N_TERM_PKA_DEFAULT: f32 = 7.5;
-    const C_TERM_PKA_DEFAULT: f32 = 3.55;
+
+    /// pKa of the selenol group of selenocysteine (Huber & Criddle, 1967), which is
+    /// not part of the published scales
+    pub const SELENOCYSTEINE_PKA: f32 = 5.24;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub enum Charge {
         Positive,
         Negative,
     }
+
+    /// A set of pKa values for the ionisable side chains and the termini
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct PkaScale {
+        /// pKa and charge (when protonated for positive groups, deprotonated for
+        /// negative ones) of each ionisable side chain
+        pub side_chains: BTreeMap<u8, (f32, Charge)>,
+        /// pKa of the N-terminal amino group, unless given in `n_term_by_residue`
+        pub n_term: f32,
+        /// pKa of the C-terminal carboxyl group, unless given in `c_term_by_residue`
+        pub c_term: f32,
+        /// Residue-specific pKa of the N-terminal amino group
+        pub n_term_by_residue: BTreeMap<u8, f32>,
+        /// Residue-specific pKa of the C-terminal carboxyl group
+        pub c_term_by_residue: BTreeMap<u8, f32>,
+    }
+
+    impl PkaScale {
+        /// A user-supplied scale, with the same terminal pKa for all residues.
+        pub fn new(side_chains: &[(u8, f32, Charge)], n_term: f32, c_term: f32) -> Self {
+            PkaScale {
+                side_chains: side_chains
+                    .iter()
+                    .map(|&(aa, pka, charge)| (aa, (pka, charge)))
+                    .collect(),
+                n_term,
+                c_term,
+                n_term_by_residue: BTreeMap::new(),
+                c_term_by_residue: BTreeMap::new(),
+            }
+        }
+
+        /// Scale with the seven usual ionisable side chains, and selenocysteine
+        #[allow(clippy::too_many_arguments)]
+        fn standard(
+            n_term: f32,
+            c_term: f32,
+            c: f32,
+            d: f32,
+            e: f32,
+            h: f32,
+            k: f32,
+            r: f32,
+            y: f32,
+        ) -> Self {
+            use Charge::*;
+            Self::new(
+                &[
+                    (b'C', c, Negative),
+                    (b'D', d, Negative),
+                    (b'E', e, Negative),
+                    (b'H', h, Positive),
+                    (b'K', k, Positive),
+                    (b'R', r, Positive),
+                    (b'Y', y, Negative),
+                    (b'U', SELENOCYSTEINE_PKA, Negative),
+                ],
+                n_term,
+                c_term,
+            )
+        }
+
+        pub fn emboss() -> Self {
+            Self::standard(8.6, 3.6, 8.5, 3.9, 4.1, 6.5, 10.8, 12.5, 10.1)
+        }
+
+        pub fn lehninger() -> Self {
+            Self::standard(9.69, 2.34, 8.33, 3.86, 4.25, 6.0, 10.5, 12.4, 10.0)
+        }
+
+        /// The scale of Bjellqvist _et al_, with residue-specific terminal pKa values,
+        /// as used by ExPASy's Compute pI/Mw and ProtParam tools
+        pub fn bjellqvist() -> Self {
+            let mut scale = Self::standard(7.5, 3.55, 9.0, 4.05, 4.45, 5.98, 10.0, 12.0, 10.0);
+            for &(aa, pka) in &[
+                (b'A', 7.59),
+                (b'M', 7.00),
+                (b'S', 6.93),
+                (b'P', 8.36),
+                (b'T', 6.82),
+                (b'V', 7.44),
+                (b'E', 7.70),
+            ] {
+                scale.n_term_by_residue.insert(aa, pka);
+            }
+            scale.c_term_by_residue.insert(b'D', 4.55);
+            scale.c_term_by_residue.insert(b'E', 4.75);
+            scale
+        }
+
+        pub fn sillero() -> Self {
+            Self::standard(8.2, 3.2, 9.0, 4.0, 4.5, 6.4, 10.4, 12.0, 10.0)
+        }
+
+        pub fn solomon() -> Self {
+            Self::standard(9.6, 2.4, 8.3, 3.9, 4.3, 6.0, 10.5, 12.5, 10.1)
+        }
+
+        pub fn rodwell() -> Self {
+            Self::standard(8.0, 3.1, 8.33, 3.68, 4.25, 6.0, 11.5, 11.5, 10.07)
+        }
+
+        /// The protein scale of Kozlowski's Isoelectric Point Calculator
+        pub fn ipc_protein() -> Self {
+            Self::standard(
+                9.094, 2.869, 7.555, 3.872, 4.412, 5.637, 9.052, 11.84, 10.85,
+            )
+        }
+
+        /// Look up a named scale (case-insensitive): `emboss`, `lehninger`,
+        /// `bjellqvist` (or `expasy`), `sillero`, `solomon`, `rodwell` or `ipc_protein`
+        pub fn from_name(name: &str) -> Option<Self> {
+            match name.to_ascii_lowercase().as_str() {
+                "emboss" => Some(Self::emboss()),
+                "lehninger" => Some(Self::lehninger()),
+                "bjellqvist" | "expasy" => Some(Self::bjellqvist()),
+                "sillero" => Some(Self::sillero()),
+                "solomon" => Some(Self::solomon()),
+                "rodwell" => Some(Self::rodwell()),
+                "ipc_protein" => Some(Self::ipc_protein()),
+                _ => None,
+            }
+        }
+
+        /// pKa of the N-terminal amino group when `aa` is the first residue
+        pub fn n_term_pka(&self, aa: u8) -> f32 {
+            self.n_term_by_residue
+                .get(&aa)
+                .cloned()
+                .unwrap_or(self.n_term)
+        }
+
+        /// pKa of the C-terminal carboxyl group when `aa` is the last residue
+        pub fn c_term_pka(&self, aa: u8) -> f32 {
+            self.c_term_by_residue
+                .get(&aa)
+                .cloned()
+                .unwrap_or(self.c_term)
+        }
+    }
+
+    impl Default for PkaScale {
+        fn default() -> Self {
+            Self::bjellqvist()
+        }
+    }
+}
+
+/// Hydropathy scales
+pub mod hydropathy {
+    use super::*;
     lazy_static! {
-        pub static ref pKa_table: BTreeMap<u8, (f32, Charge)> = {
+        /// Kyte & Doolittle (1982), used for the grand average of hydropathicity (GRAVY)
+        pub static ref KYTE_DOOLITTLE: BTreeMap<u8, f32> = {
             let mut m = BTreeMap::new();
-            m.insert(b'K', (10.0, Charge::Positive));
-            m.insert(b'R', (12.0, Charge::Positive));
-            m.insert(b'H', (5.98, Charge::Positive));
-            m.insert(b'D', (4.05, Charge::Negative));
-            m.insert(b'E', (4.45, Charge::Negative));
-            m.insert(b'C', (9.00, Charge::Negative));
-            m.insert(b'Y', (10.0, Charge::Negative));
+            m.insert(b'A', 1.8);
+            m.insert(b'R', -4.5);
+            m.insert(b'N', -3.5);
+            m.insert(b'D', -3.5);
+            m.insert(b'C', 2.5);
+            m.insert(b'Q', -3.5);
+            m.insert(b'E', -3.5);
+            m.insert(b'G', -0.4);
+            m.insert(b'H', -3.2);
+            m.insert(b'I', 4.5);
+            m.insert(b'L', 3.8);
+            m.insert(b'K', -3.9);
+            m.insert(b'M', 1.9);
+            m.insert(b'F', 2.8);
+            m.insert(b'P', -1.6);
+            m.insert(b'S', -0.8);
+            m.insert(b'T', -0.7);
+            m.insert(b'W', -0.9);
+            m.insert(b'Y', -1.3);
+            m.insert(b'V', 4.2);
             m
         };
-        pub static ref n_terminal_pKa_table: BTreeMap<u8, f32> = {
+        /// Hopp & Woods (1981) hydrophilicity
+        pub static ref HOPP_WOODS: BTreeMap<u8, f32> = {
             let mut m = BTreeMap::new();
-            m.insert(b'A', 7.59);
-            m.insert(b'M', 7.00);
-            m.insert(b'S', 6.93);
-            m.insert(b'P', 8.36);
-            m.insert(b'T', 6.82);
-            m.insert(b'V', 7.44);
-            m.insert(b'E', 7.70);
+            m.insert(b'A', -0.5);
+            m.insert(b'R', 3.0);
+            m.insert(b'N', 0.2);
+            m.insert(b'D', 3.0);
+            m.insert(b'C', -1.0);
+            m.insert(b'Q', 0.2);
+            m.insert(b'E', 3.0);
+            m.insert(b'G', 0.0);
+            m.insert(b'H', -0.5);
+            m.insert(b'I', -1.8);
+            m.insert(b'L', -1.8);
+            m.insert(b'K', 3.0);
+            m.insert(b'M', -1.3);
+            m.insert(b'F', -2.5);
+            m.insert(b'P', 0.0);
+            m.insert(b'S', 0.3);
+            m.insert(b'T', -0.4);
+            m.insert(b'W', -3.4);
+            m.insert(b'Y', -2.3);
+            m.insert(b'V', -1.5);
             m
         };
-        pub static ref c_terminal_pKa_table: BTreeMap<u8, f32> = {
+        /// Normalised consensus hydrophobicity of Eisenberg _et al_ (1984)
+        pub static ref EISENBERG: BTreeMap<u8, f32> = {
+            let mut m = BTreeMap::new();
+            m.insert(b'A', 0.62);
+            m.insert(b'R', -2.53);
+            m.insert(b'N', -0.78);
+            m.insert(b'D', -0.9);
+            m.insert(b'C', 0.29);
+            m.insert(b'Q', -0.85);
+            m.insert(b'E', -0.74);
+            m.insert(b'G', 0.48);
+            m.insert(b'H', -0.4);
+            m.insert(b'I', 1.38);
+            m.insert(b'L', 1.06);
+            m.insert(b'K', -1.5);
+            m.insert(b'M', 0.64);
+            m.insert(b'F', 1.19);
+            m.insert(b'P', 0.12);
+            m.insert(b'S', -0.18);
+            m.insert(b'T', -0.05);
+            m.insert(b'W', 0.81);
+            m.insert(b'Y', 0.26);
+            m.insert(b'V', 1.08);
+            m
+        };
+    }
+}
+
+/// Average flexibility index of Bhaskaran & Ponnuswamy (1988)
+pub mod flexibility {
+    use super::*;
+    lazy_static! {
+        /// Average flexibility of each residue
+        pub static ref BHASKARAN_PONNUSWAMY: BTreeMap<u8, f32> = {
+            let mut m = BTreeMap::new();
+            m.insert(b'A', 0.984);
+            m.insert(b'R', 1.008);
+            m.insert(b'N', 1.048);
+            m.insert(b'D', 1.068);
+            m.insert(b'C', 0.906);
+            m.insert(b'Q', 1.037);
+            m.insert(b'E', 1.094);
+            m.insert(b'G', 1.031);
+            m.insert(b'H', 0.95);
+            m.insert(b'I', 0.927);
+            m.insert(b'L', 0.935);
+            m.insert(b'K', 1.102);
+            m.insert(b'M', 0.952);
+            m.insert(b'F', 0.915);
+            m.insert(b'P', 1.049);
+            m.insert(b'S', 1.046);
+            m.insert(b'T', 0.997);
+            m.insert(b'W', 0.904);
+            m.insert(b'Y', 0.929);
+            m.insert(b'V', 0.931);
+            m
+        };
+    }
+}
+
+/// Bulkiness of Zimmerman _et al_ (1968)
+pub mod bulkiness {
+    use super::*;
+    lazy_static! {
+        /// Ratio of the side chain volume to its length
+        pub static ref ZIMMERMAN: BTreeMap<u8, f32> = {
+            let mut m = BTreeMap::new();
+            m.insert(b'A', 11.5);
+            m.insert(b'R', 14.28);
+            m.insert(b'N', 12.82);
+            m.insert(b'D', 11.68);
+            m.insert(b'C', 13.46);
+            m.insert(b'Q', 14.45);
+            m.insert(b'E', 13.57);
+            m.insert(b'G', 3.4);
+            m.insert(b'H', 13.69);
+            m.insert(b'I', 21.4);
+            m.insert(b'L', 21.4);
+            m.insert(b'K', 15.71);
+            m.insert(b'M', 16.25);
+            m.insert(b'F', 19.8);
+            m.insert(b'P', 17.43);
+            m.insert(b'S', 9.47);
+            m.insert(b'T', 15.77);
+            m.insert(b'W', 21.67);
+            m.insert(b'Y', 18.03);
+            m.insert(b'V', 21.57);
+            m
+        };
+    }
+}
+
+/// Dipeptide instability weight values (DIWV) of Guruprasad _et al_ (1990)
+pub mod instability_index {
+    /// Proteins with an instability index below this value are predicted to be stable
+    pub const STABILITY_THRESHOLD: f32 = 40.0;
+
+    /// `DIWV[i][j]` is the weight of the dipeptide `AMINO_ACIDS[i]` followed by
+    /// `AMINO_ACIDS[j]`
+    #[rustfmt::skip]
+    pub const DIWV: [[f32; 20]; 20] = [
+    //     A       C       D       E       F       G       H       I       K       L       M       N       P       Q       R       S       T       V       W       Y
+        [  1.00,  44.94,  -7.49,   1.00,   1.00,   1.00,  -7.49,   1.00,   1.00,   1.00,   1.00,   1.00,  20.26,   1.00,   1.00,   1.00,   1.00,   1.00,   1.00,   1.00], // A
+        [  1.00,   1.00,  20.26,   1.00,   1.00,   1.00,  33.60,   1.00,   1.00,  20.26,  33.60,   1.00,  20.26,  -6.54,   1.00,   1.00,  33.60,  -6.54,  24.68,   1.00], // C
+        [  1.00,   1.00,   1.00,   1.00,  -6.54,   1.00,   1.00,   1.00,  -7.49,   1.00,   1.00,   1.00,   1.00,   1.00,  -6.54,  20.26, -14.03,   1.00,   1.00,   1.00], // D
+        [  1.00,  44.94,  20.26,  33.60,   1.00,   1.00,  -6.54,  20.26,   1.00,   1.00,   1.00,   1.00,  20.26,  20.26,   1.00,  20.26,   1.00,   1.00, -14.03,   1.00], // E
+        [  1.00,   1.00,  13.34,   1.00,   1.00,   1.00,   1.00,   1.00, -14.03,   1.00,   1.00,   1.00,  20.26,   1.00,   1.00,   1.00,   1.00,   1.00,   1.00,  33.60], // F
+        [ -7.49,   1.00,   1.00,  -6.54,   1.00,  13.34,   1.00,  -7.49,  -7.49,   1.00,   1.00,  -7.49,   1.00,   1.00,   1.00,   1.00,  -7.49,   1.00,  13.34,  -7.49], // G
+        [  1.00,   1.00,   1.00,   1.00,  -9.37,  -9.37,   1.00,  44.94,  24.68,   1.00,   1.00,  24.68,  -1.88,   1.00,   1.00,   1.00,  -6.54,   1.00,  -1.88,  44.94], // H
+        [  1.00,   1.00,   1.00,  44.94,   1.00,   1.00,  13.34,   1.00,  -7.49,  20.26,   1.00,   1.00,  -1.88,   1.00,   1.00,   1.00,   1.00,  -7.49,   1.00,   1.00], // I
+        [  1.00,   1.00,   1.00,   1.00,   1.00,  -7.49,   1.00,  -7.49,   1.00,  -7.49,  33.60,   1.00,  -6.54,  24.64,  33.60,   1.00,   1.00,  -7.49,   1.00,   1.00], // K
+        [  1.00,   1.00,   1.00,   1.00,   1.00,   1.00,   1.00,   1.00,  -7.49,   1.00,   1.00,   1.00,  20.26,  33.60,  20.26,   1.00,   1.00,   1.00,  24.68,   1.00], // L
+        [ 13.34,   1.00,   1.00,   1.00,   1.00,   1.00,  58.28,   1.00,   1.00,   1.00,  -1.88,   1.00,  44.94,  -6.54,  -6.54,  44.94,  -1.88,   1.00,   1.00,  24.68], // M
+        [  1.00,  -1.88,   1.00,   1.00, -14.03, -14.03,   1.00,  44.94,  24.68,   1.00,   1.00,   1.00,  -1.88,  -6.54,   1.00,   1.00,  -7.49,   1.00,  -9.37,   1.00], // N
+        [ 20.26,  -6.54,  -6.54,  18.38,  20.26,   1.00,   1.00,   1.00,   1.00,   1.00,  -6.54,   1.00,  20.26,  20.26,  -6.54,  20.26,   1.00,  20.26,  -1.88,   1.00], // P
+        [  1.00,  -6.54,  20.26,  20.26,  -6.54,   1.00,   1.00,   1.00,   1.00,   1.00,   1.00,   1.00,  20.26,  20.26,   1.00,  44.94,   1.00,  -6.54,   1.00,  -6.54], // Q
+        [  1.00,   1.00,   1.00,   1.00,   1.00,  -7.49,  20.26,   1.00,   1.00,   1.00,   1.00,  13.34,  20.26,  20.26,  58.28,  44.94,   1.00,   1.00,  58.28,  -6.54], // R
+        [  1.00,  33.60,   1.00,  20.26,   1.00,   1.00,   1.00,   1.00,   1.00,   1.00,   1.00,   1.00,  44.94,  20.26,  20.26,  20.26,   1.00,   1.00,   1.00,   1.00], // S
+        [  1.00,   1.00,   1.00,  20.26,  13.34,  -7.49,   1.00,   1.00,   1.00,   1.00,   1.00, -14.03,   1.00,  -6.54,   1.00,   1.00,   1.00,   1.00, -14.03,   1.00], // T
+        [  1.00,   1.00, -14.03,   1.00,   1.00,  -7.49,   1.00,   1.00,  -1.88,   1.00,   1.00,   1.00,  20.26,   1.00,   1.00,   1.00,  -7.49,   1.00,   1.00,  -6.54], // V
+        [-14.03,   1.00,   1.00,   1.00,   1.00,  -9.37,  24.68,   1.00,   1.00,  13.34,  24.68,  13.34,   1.00,   1.00,   1.00,   1.00, -14.03,  -7.49,   1.00,   1.00], // W
+        [ 24.68,   1.00,  24.68,  -6.54,   1.00,  -7.49,  13.34,   1.00,   1.00,   1.00,  44.94,   1.00,  13.34,   1.00, -15.91,   1.00,  -7.49,   1.00,  -9.37,  13.34], // Y
+    ];
+}
+
+/// Estimated half-life by the N-end rule (Bachmair _et al_ 1986; Gonda _et al_ 1989;
+/// Tobias _et al_ 1991), as reported by ProtParam
+pub mod half_life {
+    use super::*;
+    use std::fmt;
+
+    #[derive(Debug, Clone, Copy, Default, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum HalfLife {
+        Minutes(f32),
+        Hours(f32),
+        /// Longer than the given number of hours
+        MoreThanHours(f32),
+        #[default]
+        Unknown,
+    }
+
+    impl fmt::Display for HalfLife {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                HalfLife::Minutes(m) => write!(f, "{} min", m),
+                HalfLife::Hours(h) => write!(f, "{} hours", h),
+                HalfLife::MoreThanHours(h) => write!(f, ">{} hours", h),
+                HalfLife::Unknown => write!(f, "?"),
+            }
+        }
+    }
+
+    lazy_static! {
+        /// Half-life given the N-terminal residue, in mammalian reticulocytes (in vitro),
+        /// yeast (in vivo) and _Escherichia coli_ (in vivo)
+        pub static ref N_END_RULE: BTreeMap<u8, [HalfLife; 3]> = {
+            use HalfLife::*;
             let mut m = BTreeMap::new();
-            m.insert(b'D', 4.55);
-            m.insert(b'E', 4.75);
+            m.insert(b'A', [Hours(4.4), MoreThanHours(20.0), MoreThanHours(10.0)]);
+            m.insert(b'R', [Hours(1.0), Minutes(2.0), Minutes(2.0)]);
+            m.insert(b'N', [Hours(1.4), Minutes(3.0), MoreThanHours(10.0)]);
+            m.insert(b'D', [Hours(1.1), Minutes(3.0), MoreThanHours(10.0)]);
+            m.insert(b'C', [Hours(1.2), MoreThanHours(20.0), MoreThanHours(10.0)]);
+            m.insert(b'Q', [Hours(0.8), Minutes(10.0), MoreThanHours(10.0)]);
+            m.insert(b'E', [Hours(1.0), Minutes(30.0), MoreThanHours(10.0)]);
+            m.insert(b'G', [Hours(30.0), MoreThanHours(20.0), MoreThanHours(10.0)]);
+            m.insert(b'H', [Hours(3.5), Minutes(10.0), MoreThanHours(10.0)]);
+            m.insert(b'I', [Hours(20.0), Minutes(30.0), MoreThanHours(10.0)]);
+            m.insert(b'L', [Hours(5.5), Minutes(3.0), Minutes(2.0)]);
+            m.insert(b'K', [Hours(1.3), Minutes(3.0), Minutes(2.0)]);
+            m.insert(b'M', [Hours(30.0), MoreThanHours(20.0), MoreThanHours(10.0)]);
+            m.insert(b'F', [Hours(1.1), Minutes(3.0), Minutes(2.0)]);
+            m.insert(b'P', [MoreThanHours(20.0), MoreThanHours(20.0), Unknown]);
+            m.insert(b'S', [Hours(1.9), MoreThanHours(20.0), MoreThanHours(10.0)]);
+            m.insert(b'T', [Hours(7.2), MoreThanHours(20.0), MoreThanHours(10.0)]);
+            m.insert(b'W', [Hours(2.8), Minutes(3.0), Minutes(2.0)]);
+            m.insert(b'Y', [Hours(2.8), Minutes(10.0), Minutes(2.0)]);
+            m.insert(b'V', [Hours(100.0), MoreThanHours(20.0), MoreThanHours(10.0)]);
             m
         };
     }
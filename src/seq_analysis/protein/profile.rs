@@ -0,0 +1,237 @@
+//! Sliding-window amino acid property profiles, in the manner of
+//! [ExPASy's ProtScale](https://web.expasy.org/protscale/).
+//!
+//! A scale assigns a value to each amino acid, e.g. one of the tables of
+//! [params](../params/index.html) or a user-supplied AAindex entry. The profile value
+//! of a residue is the weighted mean of the scale over a window centred on it. The
+//! weight decreases linearly from 1 at the centre of the window to `edge_weight` at
+//! its ends.
+//!
+//! # Example
+//!
+//! ```
+//! use bioinformatics_algorithms::seq_analysis::protein::params::hydropathy::KYTE_DOOLITTLE;
+//! use bioinformatics_algorithms::seq_analysis::protein::profile::{profile, WindowParams};
+//! let params = WindowParams { window: 3, edge_weight: 1.0 };
+//! let hydropathy = profile(b"RIIIR", &KYTE_DOOLITTLE, &params);
+//! assert_eq!(hydropathy.offset, 1);
+//! assert_eq!(hydropathy.values.len(), 3);
+//! assert!((hydropathy.values[1] - 4.5).abs() < 1e-6);
+//! ```
+
+use crate::utils::TextSlice;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowParams {
+    /// Number of residues in the window; must be odd
+    pub window: usize,
+    /// Relative weight of the residues at both ends of the window, between 0 and 1
+    pub edge_weight: f32,
+}
+
+impl Default for WindowParams {
+    /// The defaults of ProtScale: a window of 9 residues with uniform weights
+    fn default() -> Self {
+        WindowParams {
+            window: 9,
+            edge_weight: 1.0,
+        }
+    }
+}
+
+impl WindowParams {
+    /// Weight of each position of the window
+    pub fn weights(&self) -> Vec<f32> {
+        assert!(self.window % 2 == 1, "window size must be odd");
+        assert!(
+            (0.0..=1.0).contains(&self.edge_weight),
+            "edge weight must be between 0 and 1"
+        );
+        let half = self.window / 2;
+        (0..self.window)
+            .map(|i| {
+                if half == 0 {
+                    1.0
+                } else {
+                    let d = (i as f32 - half as f32).abs() / half as f32;
+                    1.0 - (1.0 - self.edge_weight) * d
+                }
+            })
+            .collect()
+    }
+}
+
+/// Profile values for consecutive residues of a sequence
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Profile {
+    /// 0-based position of the residue of the first value. Residues closer to the ends
+    /// of the sequence than half a window have no value.
+    pub offset: usize,
+    pub values: Vec<f32>,
+}
+
+impl Profile {
+    /// Iterate over `(0-based position, value)` pairs
+    pub fn iter(&self) -> impl Iterator<Item = (usize, f32)> + '_ {
+        self.values
+            .iter()
+            .enumerate()
+            .map(move |(i, &v)| (self.offset + i, v))
+    }
+
+    /// Write the profile as CSV with 1-based positions.
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "position,value")?;
+        for (pos, value) in self.iter() {
+            writeln!(writer, "{},{:.4}", pos + 1, value)?;
+        }
+        Ok(())
+    }
+
+    /// Write the profile as a simple SVG line plot of the given size, in pixels.
+    pub fn write_svg<W: Write>(&self, mut writer: W, width: u32, height: u32) -> io::Result<()> {
+        const MARGIN: f32 = 40.0;
+        let finite = self.values.iter().cloned().filter(|v| v.is_finite());
+        let min = finite.clone().fold(f32::INFINITY, f32::min);
+        let max = finite.fold(f32::NEG_INFINITY, f32::max);
+        let (min, max) = if min < max {
+            (min, max)
+        } else {
+            (min - 1.0, min + 1.0)
+        };
+        let plot_w = width as f32 - 2.0 * MARGIN;
+        let plot_h = height as f32 - 2.0 * MARGIN;
+        let n = self.values.len().max(2) - 1;
+        let x = |i: usize| MARGIN + plot_w * i as f32 / n as f32;
+        let y = |v: f32| MARGIN + plot_h * (max - v) / (max - min);
+
+        writeln!(
+            writer,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}">"#,
+            width, height
+        )?;
+        writeln!(
+            writer,
+            r#"<rect x="{m}" y="{m}" width="{}" height="{}" fill="none" stroke="black"/>"#,
+            plot_w,
+            plot_h,
+            m = MARGIN
+        )?;
+        if min < 0.0 && max > 0.0 {
+            writeln!(
+                writer,
+                r#"<line x1="{}" y1="{y}" x2="{}" y2="{y}" stroke="grey" stroke-dasharray="4"/>"#,
+                MARGIN,
+                MARGIN + plot_w,
+                y = y(0.0)
+            )?;
+        }
+        write!(writer, r#"<polyline fill="none" stroke="blue" points=""#)?;
+        for (i, &v) in self.values.iter().enumerate() {
+            if v.is_finite() {
+                write!(writer, "{:.1},{:.1} ", x(i), y(v))?;
+            }
+        }
+        writeln!(writer, r#""/>"#)?;
+        for &(v, anchor_y) in &[(max, MARGIN), (min, MARGIN + plot_h)] {
+            writeln!(
+                writer,
+                r#"<text x="{}" y="{}" font-size="10" text-anchor="end">{:.2}</text>"#,
+                MARGIN - 4.0,
+                anchor_y,
+                v
+            )?;
+        }
+        if let (Some(first), Some(last)) = (self.iter().next(), self.iter().last()) {
+            for &(pos, anchor_x) in &[(first.0, MARGIN), (last.0, MARGIN + plot_w)] {
+                writeln!(
+                    writer,
+                    r#"<text x="{}" y="{}" font-size="10" text-anchor="middle">{}</text>"#,
+                    anchor_x,
+                    MARGIN + plot_h + 14.0,
+                    pos + 1
+                )?;
+            }
+        }
+        writeln!(writer, "</svg>")
+    }
+}
+
+/// Sliding-window profile of `seq` on `scale`. Residues missing from the scale are
+/// left out of the weighted mean; windows without any known residue give `NaN`.
+pub fn profile(seq: TextSlice<'_>, scale: &BTreeMap<u8, f32>, params: &WindowParams) -> Profile {
+    let weights = params.weights();
+    let values = seq
+        .windows(params.window)
+        .map(|window| {
+            let (sum, total_weight) = window.iter().zip(weights.iter()).fold(
+                (0.0, 0.0),
+                |(sum, total_weight), (aa, &w)| match scale.get(aa) {
+                    Some(v) => (sum + w * v, total_weight + w),
+                    None => (sum, total_weight),
+                },
+            );
+            if total_weight > 0.0 {
+                sum / total_weight
+            } else {
+                f32::NAN
+            }
+        })
+        .collect();
+    Profile {
+        offset: params.window / 2,
+        values,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seq_analysis::protein::params::hydropathy::KYTE_DOOLITTLE;
+
+    #[test]
+    fn test_weights() {
+        let params = WindowParams {
+            window: 5,
+            edge_weight: 0.2,
+        };
+        let weights = params.weights();
+        let expected = [0.2, 0.6, 1.0, 0.6, 0.2];
+        for (w, e) in weights.iter().zip(expected.iter()) {
+            assert!((w - e).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_profile() {
+        let params = WindowParams {
+            window: 3,
+            edge_weight: 0.5,
+        };
+        let p = profile(b"IRXXX", &KYTE_DOOLITTLE, &params);
+        assert_eq!(p.offset, 1);
+        // (0.5 * 4.5 + 1.0 * -4.5) / 1.5
+        assert!((p.values[0] - -1.5).abs() < 1e-5);
+        assert!((p.values[1] - -4.5).abs() < 1e-5);
+        assert!(p.values[2].is_nan());
+        assert!(profile(b"II", &KYTE_DOOLITTLE, &params).values.is_empty());
+    }
+
+    #[test]
+    fn test_write() {
+        let p = Profile {
+            offset: 4,
+            values: vec![1.0, -0.5],
+        };
+        let mut csv = Vec::new();
+        p.write_csv(&mut csv).unwrap();
+        assert_eq!(csv, b"position,value\n5,1.0000\n6,-0.5000\n".to_vec());
+        let mut svg = Vec::new();
+        p.write_svg(&mut svg, 400, 200).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.starts_with("<svg") && svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains("<polyline"));
+    }
+}
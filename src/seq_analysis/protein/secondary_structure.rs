@@ -0,0 +1,400 @@
+//! Secondary structure prediction from the sequence alone: Chou–Fasman and GOR IV.
+//!
+//! Both methods assign each residue to one of three states: helix (`H`), strand
+//! (`E`) or coil (`C`).
+//!
+//! The Chou–Fasman predictor uses the published conformational parameters. It
+//! nucleates helices (4 helix formers out of 6 residues) and strands (3 strand
+//! formers out of 5), extends them while the mean propensity of 4 residues stays at
+//! least 1, keeps segments whose mean propensity exceeds 1.05 and that of the other
+//! state, and resolves overlaps in favour of the state with the higher total
+//! propensity.
+//!
+//! GOR IV predicts the state with the highest information given the residues and
+//! residue pairs within 8 positions. Its statistics must be estimated from a set of
+//! sequences of known structure, e.g. DSSP assignments of a non-redundant set of PDB
+//! chains; the original database is not bundled.
+//!
+//! # Example
+//!
+//! ```
+//! use bioinformatics_algorithms::seq_analysis::protein::secondary_structure::*;
+//! let prediction = chou_fasman(b"MSEEALKKLAEELAKKGSPDGTVKVYVIVTVNG");
+//! assert_eq!(prediction.len(), 33);
+//! let (helix, strand, coil) = fractions(&prediction);
+//! assert!((helix + strand + coil - 1.0).abs() < 1e-6);
+//! println!("{}", to_string(&prediction));
+//! ```
+//!
+//! # References
+//!
+//! - [Chou P.Y., Fasman G.D. (1978) Prediction of the secondary structure of proteins from their amino acid sequence. _Adv. Enzymol._ **47**: 45-148.](https://doi.org/10.1002/9780470122921.ch2)
+//! - [Garnier J., Gibrat J.F., Robson B. (1996) GOR method for predicting protein secondary structure from amino acid sequence. _Methods Enzymol._ **266**: 540-553.](https://doi.org/10.1016/S0076-6879(96)66034-0)
+
+use crate::seq_analysis::protein::params::amino_acid_index;
+use crate::utils::TextSlice;
+use lazy_static::lazy_static;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SecondaryStructure {
+    Helix,
+    Strand,
+    Coil,
+}
+
+impl SecondaryStructure {
+    const ALL: [SecondaryStructure; 3] = [
+        SecondaryStructure::Helix,
+        SecondaryStructure::Strand,
+        SecondaryStructure::Coil,
+    ];
+
+    /// Reduce a DSSP code to three states: `H`, `G` and `I` are helices, `E` and `B`
+    /// strands, anything else coil. `C` also stands for coil.
+    pub fn from_dssp(code: u8) -> Self {
+        match code.to_ascii_uppercase() {
+            b'H' | b'G' | b'I' => SecondaryStructure::Helix,
+            b'E' | b'B' => SecondaryStructure::Strand,
+            _ => SecondaryStructure::Coil,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            SecondaryStructure::Helix => 'H',
+            SecondaryStructure::Strand => 'E',
+            SecondaryStructure::Coil => 'C',
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// The assignment as a string of `H`, `E` and `C`
+pub fn to_string(states: &[SecondaryStructure]) -> String {
+    states.iter().map(|s| s.to_char()).collect()
+}
+
+/// Fractions of residues in helix, strand and coil
+pub fn fractions(states: &[SecondaryStructure]) -> (f32, f32, f32) {
+    let mut counts = [0usize; 3];
+    for s in states {
+        counts[s.index()] += 1;
+    }
+    let n = states.len().max(1) as f32;
+    (
+        counts[0] as f32 / n,
+        counts[1] as f32 / n,
+        counts[2] as f32 / n,
+    )
+}
+
+lazy_static! {
+    /// Chou–Fasman helix and strand propensities, times 100
+    pub static ref CHOU_FASMAN: BTreeMap<u8, (f32, f32)> = {
+        let mut m = BTreeMap::new();
+        m.insert(b'A', (142.0, 83.0));
+        m.insert(b'R', (98.0, 93.0));
+        m.insert(b'N', (67.0, 89.0));
+        m.insert(b'D', (101.0, 54.0));
+        m.insert(b'C', (70.0, 119.0));
+        m.insert(b'Q', (111.0, 110.0));
+        m.insert(b'E', (151.0, 37.0));
+        m.insert(b'G', (57.0, 75.0));
+        m.insert(b'H', (100.0, 87.0));
+        m.insert(b'I', (108.0, 160.0));
+        m.insert(b'L', (121.0, 130.0));
+        m.insert(b'K', (114.0, 74.0));
+        m.insert(b'M', (145.0, 105.0));
+        m.insert(b'F', (113.0, 138.0));
+        m.insert(b'P', (57.0, 55.0));
+        m.insert(b'S', (77.0, 75.0));
+        m.insert(b'T', (83.0, 119.0));
+        m.insert(b'W', (108.0, 137.0));
+        m.insert(b'Y', (69.0, 147.0));
+        m.insert(b'V', (106.0, 170.0));
+        m
+    };
+}
+
+fn mean(values: &[f32]) -> f32 {
+    values.iter().sum::<f32>() / values.len() as f32
+}
+
+/// Residues covered by segments nucleated by `min_formers` residues with propensity
+/// above 100 in `nucleus` consecutive residues
+fn find_segments(p: &[f32], other: &[f32], nucleus: usize, min_formers: usize) -> Vec<bool> {
+    const EXTENSION: usize = 4;
+    let n = p.len();
+    let mut covered = vec![false; n];
+    if n < nucleus {
+        return covered;
+    }
+    for i in 0..=n - nucleus {
+        if p[i..i + nucleus].iter().filter(|&&x| x > 100.0).count() < min_formers {
+            continue;
+        }
+        let (mut start, mut end) = (i, i + nucleus);
+        while end < n && mean(&p[end + 1 - EXTENSION..=end]) >= 100.0 {
+            end += 1;
+        }
+        while start > 0 && mean(&p[start - 1..start - 1 + EXTENSION]) >= 100.0 {
+            start -= 1;
+        }
+        let (p_mean, other_mean) = (mean(&p[start..end]), mean(&other[start..end]));
+        if p_mean > 105.0 && p_mean > other_mean {
+            for c in &mut covered[start..end] {
+                *c = true;
+            }
+        }
+    }
+    covered
+}
+
+/// Chou–Fasman prediction of helices and strands. Turns are not predicted and are
+/// reported as coil; unknown residues have neutral propensities.
+pub fn chou_fasman(seq: TextSlice<'_>) -> Vec<SecondaryStructure> {
+    let (pa, pb): (Vec<f32>, Vec<f32>) = seq
+        .iter()
+        .map(|aa| {
+            CHOU_FASMAN
+                .get(&aa.to_ascii_uppercase())
+                .cloned()
+                .unwrap_or((100.0, 100.0))
+        })
+        .unzip();
+    let helix = find_segments(&pa, &pb, 6, 4);
+    let strand = find_segments(&pb, &pa, 5, 3);
+
+    let mut states: Vec<_> = (0..seq.len())
+        .map(|i| match (helix[i], strand[i]) {
+            (true, false) => SecondaryStructure::Helix,
+            (false, true) => SecondaryStructure::Strand,
+            _ => SecondaryStructure::Coil,
+        })
+        .collect();
+    // overlapping runs go to the state with the higher total propensity
+    let mut i = 0;
+    while i < seq.len() {
+        if helix[i] && strand[i] {
+            let mut j = i;
+            while j < seq.len() && helix[j] && strand[j] {
+                j += 1;
+            }
+            let state = if pa[i..j].iter().sum::<f32>() >= pb[i..j].iter().sum::<f32>() {
+                SecondaryStructure::Helix
+            } else {
+                SecondaryStructure::Strand
+            };
+            for s in &mut states[i..j] {
+                *s = state;
+            }
+            i = j;
+        } else {
+            i += 1;
+        }
+    }
+    states
+}
+
+/// Half-width of the GOR window
+const GOR_HALF_WINDOW: usize = 8;
+const GOR_WINDOW: usize = 2 * GOR_HALF_WINDOW + 1;
+
+/// Statistics of the GOR IV method: counts of states given single residues and
+/// residue pairs at each position of a window of 17 residues.
+///
+/// No statistics are bundled: a predictor must be trained, with
+/// [`train`](#method.train) or with [`add`](#method.add) from
+/// [`untrained`](#method.untrained), on sequences of known structure before it gives
+/// meaningful predictions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gor {
+    /// `singles[state][offset][aa]`
+    singles: Vec<Vec<[f64; 20]>>,
+    /// `pairs[state][pair index][aa1 * 20 + aa2]`, for offsets `m < n`
+    pairs: Vec<Vec<Vec<f64>>>,
+}
+
+impl Gor {
+    /// Statistics without any observation, to be trained with [`add`](#method.add)
+    pub fn untrained() -> Self {
+        let n_pairs = GOR_WINDOW * (GOR_WINDOW - 1) / 2;
+        Gor {
+            singles: vec![vec![[0.0; 20]; GOR_WINDOW]; 3],
+            pairs: vec![vec![vec![0.0; 400]; n_pairs]; 3],
+        }
+    }
+
+    /// Estimate the statistics from sequences paired with their structures, given as
+    /// DSSP codes or `H`/`E`/`C` strings of the same length.
+    pub fn train<'a, I: IntoIterator<Item = (TextSlice<'a>, TextSlice<'a>)>>(data: I) -> Self {
+        let mut gor = Self::untrained();
+        for (seq, structure) in data {
+            gor.add(seq, structure);
+        }
+        gor
+    }
+
+    /// Add the observations of one sequence of known structure.
+    pub fn add(&mut self, seq: TextSlice<'_>, structure: TextSlice<'_>) {
+        assert_eq!(
+            seq.len(),
+            structure.len(),
+            "sequence and structure must have the same length"
+        );
+        for (j, &code) in structure.iter().enumerate() {
+            let state = SecondaryStructure::from_dssp(code).index();
+            let window = Self::window(seq, j);
+            for (m, &a) in window.iter().enumerate() {
+                let a = match a {
+                    Some(a) => a,
+                    None => continue,
+                };
+                self.singles[state][m][a] += 1.0;
+                for (n, &b) in window.iter().enumerate().skip(m + 1) {
+                    if let Some(b) = b {
+                        self.pairs[state][Self::pair_index(m, n)][a * 20 + b] += 1.0;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Predict the state of each residue.
+    pub fn predict(&self, seq: TextSlice<'_>) -> Vec<SecondaryStructure> {
+        (0..seq.len())
+            .map(|j| {
+                let window = Self::window(seq, j);
+                let scores = SecondaryStructure::ALL
+                    .iter()
+                    .map(|&s| self.information(s, &window));
+                let best = scores
+                    .enumerate()
+                    .fold((0, f64::NEG_INFINITY), |best, (k, score)| {
+                        if score > best.1 {
+                            (k, score)
+                        } else {
+                            best
+                        }
+                    });
+                SecondaryStructure::ALL[best.0]
+            })
+            .collect()
+    }
+
+    /// Residue indices in the window centred on `j`, `None` outside the sequence or
+    /// for non-standard residues
+    fn window(seq: TextSlice<'_>, j: usize) -> [Option<usize>; GOR_WINDOW] {
+        let mut window = [None; GOR_WINDOW];
+        for (m, w) in window.iter_mut().enumerate() {
+            if let Some(pos) = (j + m).checked_sub(GOR_HALF_WINDOW) {
+                if pos < seq.len() {
+                    *w = amino_acid_index(seq[pos].to_ascii_uppercase());
+                }
+            }
+        }
+        window
+    }
+
+    fn pair_index(m: usize, n: usize) -> usize {
+        // pairs (0, 1), (0, 2), ..., (0, 16), (1, 2), ...
+        m * (2 * GOR_WINDOW - m - 1) / 2 + (n - m - 1)
+    }
+
+    /// GOR IV information difference `I(S; window) - I(not S; window)`, with a
+    /// pseudocount of 1 for each count
+    fn information(&self, state: SecondaryStructure, window: &[Option<usize>]) -> f64 {
+        let s = state.index();
+        let log_odds = |count: &dyn Fn(usize) -> f64| {
+            let own = count(s) + 1.0;
+            let others: f64 = (0..3).filter(|&t| t != s).map(count).sum::<f64>() + 1.0;
+            (own / others).ln()
+        };
+        let mut pair_term = 0.0;
+        let mut single_term = 0.0;
+        for (m, &a) in window.iter().enumerate() {
+            let a = match a {
+                Some(a) => a,
+                None => continue,
+            };
+            single_term += log_odds(&|t| self.singles[t][m][a]);
+            for (n, &b) in window.iter().enumerate().skip(m + 1) {
+                if let Some(b) = b {
+                    let k = Self::pair_index(m, n);
+                    pair_term += log_odds(&|t| self.pairs[t][k][a * 20 + b]);
+                }
+            }
+        }
+        // the weights 2/17 and 15/17 of a full window, adapted to the residues present
+        // so that each residue counts once at the ends of the sequence
+        let k = window.iter().filter(|a| a.is_some()).count().max(1) as f64;
+        2.0 / k * pair_term - (k - 2.0) / k * single_term
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use SecondaryStructure::*;
+
+    #[test]
+    fn test_chou_fasman() {
+        let helix = chou_fasman(b"GSPEEALKKMAEELLKKAEEGNPG");
+        assert!(fractions(&helix).0 > 0.5);
+        assert_eq!(helix[0], Coil);
+
+        let strand = chou_fasman(b"GNPDGTVYVIVTVYVIGNPDG");
+        assert!(fractions(&strand).1 > 0.4);
+        assert_eq!(strand[10], Strand);
+
+        let coil = chou_fasman(b"GNPDGSPNGSDPGNGSPDG");
+        assert_eq!(to_string(&coil), "C".repeat(19));
+    }
+
+    #[test]
+    fn test_fractions() {
+        let (h, e, c) = fractions(&[Helix, Helix, Strand, Coil]);
+        assert_eq!((h, e, c), (0.5, 0.25, 0.25));
+        assert_eq!(fractions(&[]), (0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_pair_index() {
+        let mut k = 0;
+        for m in 0..GOR_WINDOW {
+            for n in m + 1..GOR_WINDOW {
+                assert_eq!(Gor::pair_index(m, n), k);
+                k += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn test_gor() {
+        // helices rich in A/E/K, strands in V/I/Y, coils in G/P/N
+        let training: Vec<(&[u8], &[u8])> = vec![
+            (
+                b"GPNGAEKAAEKAEKAGPNGVIVYVIVGPNG",
+                b"CCCCHHHHHHHHHHHCCCCEEEEEEECCCC",
+            ),
+            (
+                b"NGPVYIVIVGNPGKEAAKEEAKAKGPNGPG",
+                b"CCCEEEEEECCCCHHHHHHHHHHHCCCCCC",
+            ),
+            (
+                b"PGNAKEAEKAAKEGPGNIVYIVYVNGPGNP",
+                b"CCCHHHHHHHHHHCCCCEEEEEEECCCCCC",
+            ),
+        ];
+        // repeated so that the counts outweigh the pseudocounts
+        let gor = Gor::train(training.iter().cycle().take(60).cloned());
+        let prediction = gor.predict(b"GPNGKAEAKEAAKGPNGVYIVIVNPG");
+        assert_eq!(prediction[8], Helix);
+        assert_eq!(prediction[19], Strand);
+        assert_eq!(prediction[1], Coil);
+    }
+}
@@ -0,0 +1,275 @@
+//! SEG masking of low-complexity protein regions.
+//!
+//! The complexity of a window is the Shannon entropy (in bits) of its composition.
+//! Windows with a complexity of at most `locut` trigger a segment, which is extended
+//! over the overlapping windows with a complexity of at most `hicut`. Each segment is
+//! then trimmed to its subsequence with the lowest compositional probability `P0`,
+//! the probability of drawing a sequence with the same complexity state from a
+//! uniform distribution over the 20 amino acids. As in SEG, at most `maxtrim`
+//! residues are trimmed from each segment, which bounds the search on long
+//! low-complexity regions.
+//!
+//! # Example
+//!
+//! ```
+//! use bioinformatics_algorithms::seq_analysis::masking::hard_mask;
+//! use bioinformatics_algorithms::seq_analysis::protein::seg::{seg, SegParams};
+//! let seq = b"MKLVTGHEDNRIWCFAPYSQQQQQQQQQQQQQQQQQMKLVTGHEDNRIWCFAPY";
+//! let intervals = seg(seq, &SegParams::default());
+//! assert_eq!(intervals, vec![19..36]);
+//! assert_eq!(&hard_mask(seq, &intervals, b'X')[17..21], b"YSXX");
+//! ```
+//!
+//! # References
+//!
+//! - [Wootton J.C., Federhen S. (1993) Statistics of local complexity in amino acid sequences and sequence databases. _Comput. Chem._ **17**: 149-163.](https://doi.org/10.1016/0097-8485(93)85006-X)
+//! - [Wootton J.C., Federhen S. (1996) Analysis of compositionally biased regions in sequence databases. _Methods Enzymol._ **266**: 554-571.](https://doi.org/10.1016/S0076-6879(96)66035-2)
+
+use crate::seq_analysis::masking::merge_intervals;
+use crate::seq_analysis::protein::params::amino_acid_index;
+use crate::utils::TextSlice;
+use std::ops::Range;
+
+/// Size of the alphabet in the computation of `P0`
+const ALPHABET_SIZE: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SegParams {
+    /// Window length
+    pub window: usize,
+    /// Maximum complexity of a window triggering a segment, in bits
+    pub locut: f64,
+    /// Maximum complexity of a window extending a segment, in bits
+    pub hicut: f64,
+    /// Maximum number of residues trimmed from a segment
+    pub maxtrim: usize,
+}
+
+impl Default for SegParams {
+    /// The defaults of SEG: 12, 2.2, 2.5 and 100
+    fn default() -> Self {
+        SegParams {
+            window: 12,
+            locut: 2.2,
+            hicut: 2.5,
+            maxtrim: 100,
+        }
+    }
+}
+
+/// Shannon entropy, in bits, of the composition of `seq`
+pub fn complexity(seq: TextSlice<'_>) -> f64 {
+    let mut counts = [0usize; 256];
+    for &aa in seq {
+        counts[aa.to_ascii_uppercase() as usize] += 1;
+    }
+    entropy(&counts, seq.len())
+}
+
+fn entropy(counts: &[usize], len: usize) -> f64 {
+    counts
+        .iter()
+        .filter(|&&n| n > 0)
+        .map(|&n| {
+            let p = n as f64 / len as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// `ln(n!)` for `n` up to `max`
+fn ln_factorials(max: usize) -> Vec<f64> {
+    let mut res = Vec::with_capacity(max + 1);
+    res.push(0.0);
+    for n in 1..=max {
+        res.push(res[n - 1] + (n as f64).ln());
+    }
+    res
+}
+
+/// Class of a residue in the computation of `P0`: one of the 20 amino acids, or
+/// `ALPHABET_SIZE` for any other residue
+fn residue_class(aa: u8) -> usize {
+    amino_acid_index(aa.to_ascii_uppercase()).unwrap_or(ALPHABET_SIZE)
+}
+
+/// Composition of a subsequence, with the terms of `ln(P0)` updated as residues are
+/// added or removed
+#[derive(Debug, Clone)]
+struct Composition {
+    counts: [usize; ALPHABET_SIZE + 1],
+    len: usize,
+    /// Number of residue classes present
+    present: usize,
+    /// Sum of `ln(n!)` over the counts `n`
+    ln_fact_counts: f64,
+    /// Number of residue classes with each count
+    classes: Vec<usize>,
+    /// Sum of `ln(c!)` over the numbers of classes `c` with the same (non-zero) count
+    ln_fact_classes: f64,
+}
+
+impl Composition {
+    fn new(seq: TextSlice<'_>, ln_fact: &[f64]) -> Self {
+        let mut res = Composition {
+            counts: [0; ALPHABET_SIZE + 1],
+            len: 0,
+            present: 0,
+            ln_fact_counts: 0.0,
+            classes: vec![0; seq.len() + 1],
+            ln_fact_classes: 0.0,
+        };
+        for &aa in seq {
+            res.add(residue_class(aa), ln_fact);
+        }
+        res
+    }
+
+    /// Move one class from count `from` to count `to`
+    fn move_class(&mut self, from: usize, to: usize, ln_fact: &[f64]) {
+        if from > 0 {
+            let c = self.classes[from];
+            self.ln_fact_classes -= ln_fact[c] - ln_fact[c - 1];
+            self.classes[from] -= 1;
+        }
+        if to > 0 {
+            let c = self.classes[to];
+            self.ln_fact_classes += ln_fact[c + 1] - ln_fact[c];
+            self.classes[to] += 1;
+        }
+    }
+
+    fn add(&mut self, class: usize, ln_fact: &[f64]) {
+        let n = self.counts[class];
+        self.ln_fact_counts += ln_fact[n + 1] - ln_fact[n];
+        self.move_class(n, n + 1, ln_fact);
+        if n == 0 {
+            self.present += 1;
+        }
+        self.counts[class] += 1;
+        self.len += 1;
+    }
+
+    fn remove(&mut self, class: usize, ln_fact: &[f64]) {
+        let n = self.counts[class];
+        self.ln_fact_counts -= ln_fact[n] - ln_fact[n - 1];
+        self.move_class(n, n - 1, ln_fact);
+        if n == 1 {
+            self.present -= 1;
+        }
+        self.counts[class] -= 1;
+        self.len -= 1;
+    }
+
+    /// `ln(P0)` of the composition
+    fn ln_p0(&self, ln_fact: &[f64]) -> f64 {
+        let alphabet = ALPHABET_SIZE.max(self.present);
+        // number of sequences with this composition
+        let ln_omega = ln_fact[self.len] - self.ln_fact_counts;
+        // number of compositions with the same complexity state
+        let ln_f = ln_fact[alphabet] - ln_fact[alphabet - self.present] - self.ln_fact_classes;
+        ln_omega + ln_f - self.len as f64 * (alphabet as f64).ln()
+    }
+}
+
+/// Subsequence of `seq[range]` with the lowest `P0`, trimming at most `maxtrim`
+/// residues
+fn trim(seq: TextSlice<'_>, range: Range<usize>, maxtrim: usize, ln_fact: &[f64]) -> Range<usize> {
+    let min_len = range.len().saturating_sub(maxtrim).max(1);
+    let mut best = (range.clone(), f64::INFINITY);
+    let mut suffix = Composition::new(&seq[range.clone()], ln_fact);
+    for start in range.start..=range.end - min_len {
+        let mut composition = suffix.clone();
+        for end in (start + min_len..=range.end).rev() {
+            let p = composition.ln_p0(ln_fact);
+            if p < best.1 {
+                best = (start..end, p);
+            }
+            composition.remove(residue_class(seq[end - 1]), ln_fact);
+        }
+        suffix.remove(residue_class(seq[start]), ln_fact);
+    }
+    best.0
+}
+
+/// Low-complexity segments of `seq`, as sorted, non-overlapping, 0-based half-open
+/// ranges.
+pub fn seg(seq: TextSlice<'_>, params: &SegParams) -> Vec<Range<usize>> {
+    assert!(params.window > 0, "window must not be empty");
+    assert!(params.locut <= params.hicut, "locut must not exceed hicut");
+    if seq.len() < params.window {
+        return Vec::new();
+    }
+    let complexities: Vec<f64> = seq.windows(params.window).map(complexity).collect();
+    let mut raw = Vec::new();
+    let mut i = 0;
+    while i < complexities.len() {
+        if complexities[i] > params.locut {
+            i += 1;
+            continue;
+        }
+        let mut first = i;
+        while first > 0 && complexities[first - 1] <= params.hicut {
+            first -= 1;
+        }
+        let mut last = i;
+        while last + 1 < complexities.len() && complexities[last + 1] <= params.hicut {
+            last += 1;
+        }
+        raw.push(first..last + params.window);
+        i = last + 1;
+    }
+    let ln_fact = ln_factorials(seq.len().max(ALPHABET_SIZE + 1));
+    merge_intervals(
+        merge_intervals(raw)
+            .into_iter()
+            .map(|range| trim(seq, range, params.maxtrim, &ln_fact))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complexity() {
+        assert_eq!(complexity(b"QQQQ"), 0.0);
+        assert!((complexity(b"ACDEFGHIKLMN") - 12f64.log2()).abs() < 1e-9);
+        assert!((complexity(b"AACC") - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ln_p0() {
+        let ln_fact = ln_factorials(21);
+        let ln_p0 = |seq: &[u8]| Composition::new(seq, &ln_fact).ln_p0(&ln_fact);
+        // a single residue: 20 compositions of 1 sequence each, out of 20
+        assert!(ln_p0(b"A").abs() < 1e-9);
+        // "AA": 20 compositions out of 400 sequences
+        assert!((ln_p0(b"AA") - (1.0f64 / 20.0).ln()).abs() < 1e-9);
+        // "AC": 190 compositions of 2 sequences, out of 400
+        assert!((ln_p0(b"AC") - (380.0f64 / 400.0).ln()).abs() < 1e-9);
+        // removing residues gives the composition of the remaining ones
+        let mut composition = Composition::new(b"ACCAQDQ", &ln_fact);
+        for &aa in b"QAD" {
+            composition.remove(residue_class(aa), &ln_fact);
+        }
+        assert!((composition.ln_p0(&ln_fact) - ln_p0(b"ACCQ")).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_seg() {
+        let params = SegParams::default();
+        assert!(seg(b"MKLVTGHEDNRIWCFAPYSMKLVTGHEDNRIWCFAPY", &params).is_empty());
+        let seq = b"MKLVTGHEDNRIWCFAPYSPAPAPAPAPAPAPAPAPAPAPMKLVTGHEDNRIWCFAPY";
+        assert_eq!(seg(seq, &params), vec![19..40]);
+        assert!(seg(b"QQQ", &params).is_empty());
+    }
+
+    #[test]
+    fn test_long_segment() {
+        let flank = b"MKLVTGHEDNRIWCFAPYS";
+        let seq = [&flank[..], &[b'Q'; 5000], flank].concat();
+        assert_eq!(seg(&seq, &SegParams::default()), vec![19..5019]);
+    }
+}
@@ -0,0 +1,297 @@
+//! Prediction of transmembrane helices and membrane topology.
+//!
+//! Transmembrane (TM) helices are predicted as the stretches where the Kyte &
+//! Doolittle hydropathy, averaged over a window about the length of a helix crossing
+//! the membrane, stays above a threshold. The topology then follows the positive-inside
+//! rule: loops alternate between both sides of the membrane, and the side whose loops
+//! carry more lysines and arginines next to the TM segments is the cytoplasmic one.
+//!
+//! # Example
+//!
+//! ```
+//! use bioinformatics_algorithms::seq_analysis::protein::transmembrane::{predict, Side, TmParams};
+//! let seq = b"MSKKRGDNSTWQLLVALLAILAIVLFAVVALIGLYKRKDGSE";
+//! let topology = predict(seq, &TmParams::default());
+//! assert_eq!(topology.segments.len(), 1);
+//! assert_eq!(topology.n_terminus, Side::Inside);
+//! assert!(topology.to_string().starts_with('i'));
+//! ```
+//!
+//! # References
+//!
+//! - [Kyte J., Doolittle R.F. (1982) A simple method for displaying the hydropathic character of a protein. _J. Mol. Biol._ **157**: 105-132.](https://doi.org/10.1016/0022-2836(82)90515-0)
+//! - [von Heijne G. (1992) Membrane protein structure prediction: hydrophobicity analysis and the positive-inside rule. _J. Mol. Biol._ **225**: 487-494.](https://doi.org/10.1016/0022-2836(92)90934-C)
+
+use crate::seq_analysis::protein::params::hydropathy::KYTE_DOOLITTLE;
+use crate::seq_analysis::protein::profile::{profile, WindowParams};
+use crate::utils::TextSlice;
+use std::fmt;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TmParams {
+    /// Window of the hydropathy profile; must be odd
+    pub window: usize,
+    /// Minimum mean hydropathy of a window in a TM segment
+    pub threshold: f32,
+    /// Number of loop residues on each side of a TM segment in which positive
+    /// charges are counted
+    pub flank: usize,
+    /// Maximum length of a TM segment, at least the window; longer hydrophobic
+    /// stretches, such as helical hairpins with short loops, are split at their least
+    /// hydrophobic window
+    pub max_length: usize,
+}
+
+impl Default for TmParams {
+    /// The window and threshold recommended by Kyte & Doolittle, the flanking region
+    /// used by von Heijne, and segments of at most 35 residues
+    fn default() -> Self {
+        TmParams {
+            window: 19,
+            threshold: 1.6,
+            flank: 15,
+            max_length: 35,
+        }
+    }
+}
+
+/// Side of the membrane
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// Cytoplasmic
+    Inside,
+    Outside,
+}
+
+impl Side {
+    pub fn opposite(self) -> Self {
+        match self {
+            Side::Inside => Side::Outside,
+            Side::Outside => Side::Inside,
+        }
+    }
+}
+
+/// Predicted membrane topology
+#[derive(Debug, Clone, PartialEq)]
+pub struct Topology {
+    /// 0-based, half-open ranges of the TM segments, in order
+    pub segments: Vec<Range<usize>>,
+    /// Side of the N-terminus
+    pub n_terminus: Side,
+}
+
+impl Topology {
+    /// Side of the loop following the `k`-th (0-based) TM segment
+    pub fn side_after(&self, k: usize) -> Side {
+        if k % 2 == 1 {
+            self.n_terminus
+        } else {
+            self.n_terminus.opposite()
+        }
+    }
+
+    /// Side of the C-terminus
+    pub fn c_terminus(&self) -> Side {
+        match self.segments.len() {
+            0 => self.n_terminus,
+            n => self.side_after(n - 1),
+        }
+    }
+}
+
+impl fmt::Display for Topology {
+    /// TMHMM-like notation with 1-based inclusive positions, e.g. `i7-29o44-66i`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = |side| match side {
+            Side::Inside => 'i',
+            Side::Outside => 'o',
+        };
+        write!(f, "{}", symbol(self.n_terminus))?;
+        for (k, segment) in self.segments.iter().enumerate() {
+            write!(
+                f,
+                "{}-{}{}",
+                segment.start + 1,
+                segment.end,
+                symbol(self.side_after(k))
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Predict the TM segments of `seq` and its topology.
+pub fn predict(seq: TextSlice<'_>, params: &TmParams) -> Topology {
+    assert!(
+        params.max_length >= params.window,
+        "the maximum segment length must be at least the window size"
+    );
+    let half = params.window / 2;
+    let hydropathy = profile(
+        seq,
+        &KYTE_DOOLITTLE,
+        &WindowParams {
+            window: params.window,
+            edge_weight: 1.0,
+        },
+    );
+
+    // runs of consecutive window centres above the threshold
+    let mut runs: Vec<Range<usize>> = Vec::new();
+    for (pos, value) in hydropathy.iter() {
+        if value >= params.threshold {
+            match runs.last_mut() {
+                Some(last) if last.end == pos => last.end = pos + 1,
+                _ => runs.push(pos..pos + 1),
+            }
+        }
+    }
+    // extended by half a window, without overlapping the previous segment
+    let mut segments: Vec<Range<usize>> = Vec::new();
+    for (k, run) in runs.iter().enumerate() {
+        let mut start = run.start - half;
+        if k > 0 && start < segments[k - 1].end {
+            let boundary = (runs[k - 1].end + run.start) / 2;
+            segments[k - 1].end = boundary;
+            start = boundary;
+        }
+        segments.push(start..run.end + half);
+    }
+    let value = |pos: usize| hydropathy.values[pos - hydropathy.offset];
+    let mut k = 0;
+    while k < segments.len() {
+        let segment = segments[k].clone();
+        if segment.len() <= params.max_length {
+            k += 1;
+            continue;
+        }
+        // drop the residue at the centre of the least hydrophobic window, keeping
+        // pieces of at least half a window
+        let split = (segment.start + half..segment.end - half)
+            .min_by(|&a, &b| value(a).partial_cmp(&value(b)).unwrap())
+            .unwrap();
+        segments.splice(k..=k, vec![segment.start..split, split + 1..segment.end]);
+    }
+
+    // positive charges in the flanking regions of loops of even and odd rank
+    let mut positives = [0, 0];
+    let n_loops = segments.len() + 1;
+    for k in 0..n_loops {
+        let start = if k == 0 { 0 } else { segments[k - 1].end };
+        let end = if k == segments.len() {
+            seq.len()
+        } else {
+            segments[k].start
+        };
+        let mut flanks = Vec::new();
+        if k > 0 {
+            flanks.push(start..end.min(start + params.flank));
+        }
+        if k < segments.len() {
+            flanks.push(end.saturating_sub(params.flank).max(start)..end);
+        }
+        if flanks.len() == 2 && flanks[0].end > flanks[1].start {
+            // short loop: count each residue once
+            flanks.clear();
+            flanks.push(start..end);
+        }
+        for flank in flanks {
+            positives[k % 2] += seq[flank]
+                .iter()
+                .filter(|&&aa| aa == b'K' || aa == b'R')
+                .count();
+        }
+    }
+    let n_terminus = if positives[1] > positives[0] {
+        Side::Outside
+    } else {
+        Side::Inside
+    };
+    Topology {
+        segments,
+        n_terminus,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // two hydrophobic stretches, with lysines in the N- and C-terminal loops
+    static TWO_TM: &[u8] =
+        b"MKKRKSGDNLLIVALLAVLIAVFLGLVAYGDPSEDGTNESGSDTPEFDSLLIALAVVLFLAIVAILGYRKKRKS";
+
+    #[test]
+    fn test_predict() {
+        let topology = predict(TWO_TM, &TmParams::default());
+        assert_eq!(topology.segments.len(), 2);
+        assert_eq!(topology.n_terminus, Side::Inside);
+        assert_eq!(topology.side_after(0), Side::Outside);
+        assert_eq!(topology.c_terminus(), Side::Inside);
+        for segment in &topology.segments {
+            assert!(segment.len() >= 19);
+        }
+        assert!(topology.segments[0].start < 12 && topology.segments[0].end > 25);
+    }
+
+    #[test]
+    fn test_hairpin() {
+        // two 20-residue helices separated by a 6-residue loop
+        let seq = b"MSKKRGDNSLLIVALLAVLIAVFLGLVAYNGKDGSLLIALAVVLFLAIVAILGYDESGSE";
+        let topology = predict(seq, &TmParams::default());
+        assert_eq!(topology.segments.len(), 2);
+        for segment in &topology.segments {
+            assert!(segment.len() <= 35);
+        }
+        let loop_ = 29..35;
+        assert!(topology.segments[0].end <= loop_.end && topology.segments[1].start >= loop_.start);
+        assert_eq!(topology.n_terminus, Side::Inside);
+        assert_eq!(topology.c_terminus(), Side::Inside);
+
+        let params = TmParams {
+            max_length: 19,
+            ..Default::default()
+        };
+        let topology = predict(seq, &params);
+        assert!(topology.segments.iter().all(|s| s.len() <= 19));
+    }
+
+    #[test]
+    #[should_panic(expected = "at least the window size")]
+    fn test_max_length_below_window() {
+        let seq = b"MSKKLLIVALLAVLIAVFLGLVAYNGKDE";
+        let params = TmParams {
+            max_length: 10,
+            ..Default::default()
+        };
+        predict(seq, &params);
+    }
+
+    #[test]
+    fn test_positive_inside() {
+        // the same segments with the positive charges moved to the middle loop
+        let seq = b"MSESGSDGDNLLIVALLAVLIAVFLGLVAYGKPRKNKSGRKTPKFKSLLIALAVVLFLAIVAILGYDESDES";
+        let topology = predict(seq, &TmParams::default());
+        assert_eq!(topology.segments.len(), 2);
+        assert_eq!(topology.n_terminus, Side::Outside);
+        assert_eq!(topology.c_terminus(), Side::Outside);
+    }
+
+    #[test]
+    fn test_soluble() {
+        let topology = predict(b"MSDEKRKLEEAQKKAEEDRKSGNPE", &TmParams::default());
+        assert!(topology.segments.is_empty());
+        assert_eq!(topology.to_string(), "i");
+    }
+
+    #[test]
+    fn test_display() {
+        let topology = Topology {
+            segments: vec![6..29, 43..66],
+            n_terminus: Side::Inside,
+        };
+        assert_eq!(topology.to_string(), "i7-29o44-66i");
+    }
+}
@@ -1 +1,46 @@
 pub type TextSlice<'a> = &'a [u8];
+
+/// Serialize byte strings, such as sequences, as strings rather than as arrays of
+/// numbers
+#[cfg(feature = "serde")]
+pub(crate) mod serde_text {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(text: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&String::from_utf8_lossy(text))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        String::deserialize(deserializer).map(String::into_bytes)
+    }
+}
+
+/// Serialize maps indexed by residues with one-character string keys
+#[cfg(feature = "serde")]
+pub(crate) mod serde_residue_map {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::BTreeMap;
+
+    pub fn serialize<S, V>(map: &BTreeMap<u8, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        V: Serialize,
+    {
+        serializer.collect_map(map.iter().map(|(&aa, v)| (aa as char, v)))
+    }
+
+    pub fn deserialize<'de, D, V>(deserializer: D) -> Result<BTreeMap<u8, V>, D::Error>
+    where
+        D: Deserializer<'de>,
+        V: Deserialize<'de>,
+    {
+        BTreeMap::<String, V>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(aa, v)| match aa.as_bytes() {
+                &[aa] => Ok((aa, v)),
+                _ => Err(D::Error::custom(format!("invalid residue '{}'", aa))),
+            })
+            .collect()
+    }
+}